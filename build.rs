@@ -5,14 +5,23 @@ extern crate bindgen;
 use std::fs::File;
 #[cfg(feature = "with-bindgen")]
 use std::io::prelude::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 fn main() {
+    println!("cargo:rerun-if-env-changed=YARA2_RUST_MODULES");
+    let rust_modules = if cfg!(feature = "static") {
+        rust_modules()
+    } else {
+        Vec::new()
+    };
+    println!(
+        "cargo:rustc-env=YARA2_RUST_MODULES={}",
+        rust_modules.join(",")
+    );
+
     #[cfg(feature = "with-bindgen")]
     {
-        use std::path::PathBuf;
-
         if !Path::new("yara/.git").exists() {
             let _ = Command::new("git")
                 .args(&["submodule", "update", "--init"])
@@ -24,20 +33,57 @@ fn main() {
             // bindings for.
             .header("yara/libyara/include/yara.h")
             .whitelist_type("YR_RULE")
+            .whitelist_type("YR_OBJECT")
+            .whitelist_type("YR_OBJECT_STRUCTURE")
+            .whitelist_type("YR_OBJECT_ARRAY")
+            .whitelist_type("YR_OBJECT_DICTIONARY")
+            .whitelist_type("YR_OBJECT_FUNCTION")
+            .whitelist_function("yr_object_create")
+            .whitelist_function("yr_object_function_create")
+            .whitelist_function("yr_object_set_.*")
+            .whitelist_var("OBJECT_TYPE_.*")
+            .whitelist_var("UNDEFINED")
+            .whitelist_type("YR_MODULE_IMPORT")
+            .whitelist_var("CALLBACK_.*")
+            .whitelist_var("RULE_TFLAGS_MATCH")
+            .whitelist_var("RULE_GFLAGS_NULL")
+            .whitelist_var("RULE_GFLAGS_DISABLED")
+            .whitelist_var("ERROR_.*")
             .whitelist_var("META_TYPE_.*")
-            .whitelist_var("STRING_GFLAGS_NULL")
+            .whitelist_var("YARA_ERROR_LEVEL_.*")
+            .whitelist_var("SCAN_FLAGS_.*")
+            .whitelist_var("STRING_GFLAGS_.*")
             .whitelist_function("yr_get_tidx")
             .whitelist_function("yr_initialize")
             .whitelist_function("yr_finalize")
             .whitelist_function("yr_rules_destroy")
+            .whitelist_function("yr_rule_disable")
+            .whitelist_function("yr_rule_enable")
+            .whitelist_function("yr_rules_save")
+            .whitelist_function("yr_rules_save_stream")
+            .whitelist_function("yr_rules_load")
+            .whitelist_function("yr_rules_load_stream")
             .whitelist_function("yr_rules_scan_mem")
             .whitelist_function("yr_rules_scan_fd")
             .whitelist_function("yr_rules_scan_proc")
+            .whitelist_function("yr_scanner_create")
+            .whitelist_function("yr_scanner_destroy")
+            .whitelist_function("yr_scanner_set_callback")
+            .whitelist_function("yr_scanner_set_timeout")
+            .whitelist_function("yr_scanner_set_flags")
+            .whitelist_function("yr_scanner_define_.*_variable")
+            .whitelist_function("yr_scanner_scan_mem")
+            .whitelist_function("yr_scanner_scan_fd")
+            .whitelist_function("yr_scanner_scan_proc")
+            .whitelist_function("yr_scanner_scan_mem_blocks")
             .whitelist_function("yr_compiler_add_string")
+            .whitelist_function("yr_compiler_add_fd")
             .whitelist_function("yr_compiler_create")
             .whitelist_function("yr_compiler_destroy")
+            .whitelist_function("yr_compiler_define_.*_variable")
             .whitelist_function("yr_compiler_get_rules")
             .whitelist_function("yr_compiler_set_callback")
+            .opaque_type("YR_SCANNER")
             .clang_arg("-I./yara/libyara/include")
             .trust_clang_mangling(false)
             // disable layout tests due to cross platform requirements
@@ -74,7 +120,14 @@ fn main() {
 
         let target = std::env::var("TARGET").unwrap();
 
-        let mut build = static_compile_get_files();
+        let mut build = static_compile_get_files(&rust_modules);
+
+        if cfg!(feature = "profiling") {
+            // yara 3.x checks PROFILING_ENABLED, later versions YR_PROFILING_ENABLED
+            build
+                .define("PROFILING_ENABLED", None)
+                .define("YR_PROFILING_ENABLED", None);
+        }
 
         if target.contains("windows") {
             println!("Building Windows");
@@ -160,9 +213,111 @@ fn make_it(it: &cc::Build) -> cc::Build {
     it.clone()
 }
 
-fn static_compile_get_files() -> cc::Build {
+/// Names of the modules written in rust to build into libyara, from the
+/// comma separated `YARA2_RUST_MODULES` environment variable, plus the
+/// module of the tests with the `test-modules` feature
+fn rust_modules() -> Vec<String> {
+    let names = std::env::var("YARA2_RUST_MODULES").unwrap_or_default();
+    let mut names = names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    // module used by tests/custom_module.rs
+    if cfg!(feature = "test-modules") && !names.iter().any(|name| name == "rust_tests") {
+        names.push("rust_tests".to_string());
+    }
+    for name in &names {
+        let valid = name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && !name.starts_with(|c: char| c.is_ascii_digit());
+        assert!(valid, "invalid module name in YARA2_RUST_MODULES: {}", name);
+    }
+    names
+}
+
+/// Generate the glue registering modules written in rust into libyara
+///
+/// libyara lists its modules in `modules/module_list`, a copy including
+/// the original and adding the rust modules is generated in an include
+/// directory searched before the libyara sources. Each module then gets C
+/// entry points forwarding to the rust registry.
+fn rust_modules_glue(build: &mut cc::Build, names: &[String]) {
+    if names.is_empty() {
+        return;
+    }
+
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("rust_modules");
+    std::fs::create_dir_all(out_dir.join("include").join("modules")).unwrap();
+    let original = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap())
+        .join("yara/libyara/modules/module_list");
+
+    let mut module_list = format!("#include \"{}\"\n", original.display());
+    let mut glue = String::from(
+        "#include <yara/modules.h>\n\n\
+         int yara2_rust_module_declarations(const char* name, YR_OBJECT* module);\n\
+         int yara2_rust_module_load(const char* name, YR_OBJECT* module_object, \
+         const uint8_t* data, size_t data_size, void* module_data, size_t module_data_size);\n",
+    );
+    for name in names {
+        module_list.push_str(&format!("MODULE({})\n", name));
+        glue.push_str(&format!(
+            r#"
+int {name}__declarations(YR_OBJECT* module)
+{{
+  return yara2_rust_module_declarations("{name}", module);
+}}
+
+int {name}__load(
+    YR_SCAN_CONTEXT* context,
+    YR_OBJECT* module_object,
+    void* module_data,
+    size_t module_data_size)
+{{
+  YR_MEMORY_BLOCK* block = first_memory_block(context);
+  const uint8_t* data = block == NULL ? NULL : block->fetch_data(block);
+
+  return yara2_rust_module_load(
+      "{name}",
+      module_object,
+      data,
+      data == NULL ? 0 : block->size,
+      module_data,
+      module_data_size);
+}}
+
+int {name}__unload(YR_OBJECT* module_object)
+{{
+  return ERROR_SUCCESS;
+}}
+
+int {name}__initialize(YR_MODULE* module)
+{{
+  return ERROR_SUCCESS;
+}}
+
+int {name}__finalize(YR_MODULE* module)
+{{
+  return ERROR_SUCCESS;
+}}
+"#,
+            name = name
+        ));
+    }
+
+    std::fs::write(out_dir.join("include/modules/module_list"), module_list).unwrap();
+    std::fs::write(out_dir.join("rust_modules.c"), glue).unwrap();
+    build
+        .include(out_dir.join("include"))
+        .file(out_dir.join("rust_modules.c"));
+}
+
+fn static_compile_get_files(rust_modules: &[String]) -> cc::Build {
+    let mut build = cc::Build::new();
+    // Added first so the generated module list shadows the libyara one
+    rust_modules_glue(&mut build, rust_modules);
     make_it(
-        cc::Build::new()
+        build
             .warnings(false)
             .file("./yara/libyara/arena.c")
             .file("./yara/libyara/re.c")
@@ -0,0 +1,178 @@
+use crate::rules::MAX_SCAN_THREADS;
+use crate::{DirectoryScanner, Error, FileScan, Result, Rule, Rules, ScanOptions, Scanner};
+use std::panic;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task;
+
+/// Scans from async code without blocking the executor
+///
+/// Scans run on tokio's blocking thread pool, at most `max_concurrent_scans`
+/// at a time, the others waiting for their turn without holding a thread.
+///
+/// Dropping the future of a scan cancels it if it has not started yet. A
+/// scan already running in libyara cannot be interrupted, it finishes in the
+/// background, within the timeout of the scan options, and its result is
+/// discarded.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use yara2::*;
+/// # async fn scan() {
+/// let mut compiler = Compiler::new().unwrap();
+/// compiler.add_rule_str("rule always { condition: true }", None).unwrap();
+/// let rules = Arc::new(compiler.compile().unwrap());
+///
+/// let scanner = AsyncScanner::new(rules, 4);
+/// let matches = scanner.scan_file_async("/bin/ls").await.unwrap();
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AsyncScanner {
+    rules: Arc<Rules>,
+    options: ScanOptions,
+    permits: Arc<Semaphore>,
+}
+
+impl AsyncScanner {
+    /// Create an async scanner for the given rules
+    ///
+    /// Clones of the scanner share the same limit of concurrent scans, which
+    /// is capped to the 32 libyara allows.
+    ///
+    /// # Arguments
+    /// `rules` - rules to scan with
+    /// `max_concurrent_scans` - number of scans running at the same time
+    pub fn new(rules: Arc<Rules>, max_concurrent_scans: usize) -> AsyncScanner {
+        AsyncScanner {
+            rules,
+            options: ScanOptions::default(),
+            permits: Arc::new(Semaphore::new(
+                max_concurrent_scans.clamp(1, MAX_SCAN_THREADS),
+            )),
+        }
+    }
+
+    /// Set the options used by the following scans
+    pub fn set_options(&mut self, options: ScanOptions) -> &mut Self {
+        self.options = options;
+        self
+    }
+
+    /// Scan a buffer without blocking the executor
+    ///
+    /// # Arguments
+    /// `data` - data to scan, moved to the thread running the scan
+    pub async fn scan_memory_async<D: Into<Vec<u8>>>(&self, data: D) -> Result<Vec<Rule>> {
+        let data = data.into();
+        self.run(move |scanner| scanner.scan_memory(&data)).await
+    }
+
+    /// Scan a file without blocking the executor
+    ///
+    /// # Arguments
+    /// `path` - path to file to scan
+    pub async fn scan_file_async<P: AsRef<Path>>(&self, path: P) -> Result<Vec<Rule>> {
+        let path = path.as_ref().to_path_buf();
+        self.run(move |scanner| scanner.scan_file(&path)).await
+    }
+
+    /// Wait for a permit, then run `scan` on the blocking thread pool
+    async fn run<F>(&self, scan: F) -> Result<Vec<Rule>>
+    where
+        F: FnOnce(&mut Scanner) -> Result<Vec<Rule>> + Send + 'static,
+    {
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| Error::Cancelled)?;
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let _cancel = CancelOnDrop(cancelled.clone());
+        let rules = self.rules.clone();
+        let options = self.options;
+
+        let scan = task::spawn_blocking(move || {
+            let _permit = permit;
+            if cancelled.load(Ordering::Relaxed) {
+                return Ok(Vec::new());
+            }
+            let mut scanner = rules.scanner()?;
+            scanner.set_options(options);
+            scan(&mut scanner)
+        });
+
+        match scan.await {
+            Ok(result) => result,
+            Err(err) => match err.try_into_panic() {
+                Ok(payload) => panic::resume_unwind(payload),
+                // the runtime is shutting down
+                Err(_) => Err(Error::Cancelled),
+            },
+        }
+    }
+}
+
+/// Flags a scan as cancelled when its future is dropped
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+impl DirectoryScanner {
+    /// Start scanning the files under `path`, receiving the results from
+    /// async code
+    ///
+    /// The files are scanned as with [`DirectoryScanner::scan`], on the
+    /// threads of the directory scanner. Dropping the returned
+    /// [`AsyncDirectoryScan`] stops the scan.
+    ///
+    /// Unlike the scans of an [`AsyncScanner`], the number of files scanned
+    /// at once is set by [`DirectoryScanner::set_threads`], not by a limit
+    /// of concurrent scans, and one thread of tokio's blocking pool is held
+    /// for the whole walk to forward the results.
+    ///
+    /// Must be called from within a tokio runtime.
+    ///
+    /// # Arguments
+    /// `path` - directory to scan
+    pub fn scan_async<P: AsRef<Path>>(&self, path: P) -> AsyncDirectoryScan {
+        let scan = self.scan(path);
+        // the directory scan buffers the results already
+        let (sender, receiver) = mpsc::channel(1);
+        task::spawn_blocking(move || {
+            for result in scan {
+                if sender.blocking_send(result).is_err() {
+                    break;
+                }
+            }
+        });
+        AsyncDirectoryScan { receiver }
+    }
+}
+
+/// Results of a [`DirectoryScanner`], as they come, for async code
+pub struct AsyncDirectoryScan {
+    receiver: mpsc::Receiver<FileScan>,
+}
+
+impl AsyncDirectoryScan {
+    /// Next file scanned, `None` once all the files have been scanned
+    pub async fn recv(&mut self) -> Option<FileScan> {
+        self.receiver.recv().await
+    }
+
+    /// Channel receiving the results
+    pub fn into_receiver(self) -> mpsc::Receiver<FileScan> {
+        self.receiver
+    }
+}
@@ -0,0 +1,128 @@
+use crate::rules::MAX_SCAN_THREADS;
+use crate::{Result, Rule, Rules, ScanOptions, Scanner};
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Data to scan with [`Rules::scan_many`]
+#[derive(Clone, Debug)]
+pub enum ScanInput {
+    Memory(Vec<u8>),
+    File(PathBuf),
+}
+
+impl ScanInput {
+    fn scan(&self, scanner: &mut Scanner) -> Result<Vec<Rule>> {
+        match self {
+            ScanInput::Memory(data) => scanner.scan_memory(data),
+            ScanInput::File(path) => scanner.scan_file(path),
+        }
+    }
+}
+
+impl From<Vec<u8>> for ScanInput {
+    fn from(data: Vec<u8>) -> Self {
+        ScanInput::Memory(data)
+    }
+}
+
+impl From<&[u8]> for ScanInput {
+    fn from(data: &[u8]) -> Self {
+        ScanInput::Memory(data.to_vec())
+    }
+}
+
+impl From<PathBuf> for ScanInput {
+    fn from(path: PathBuf) -> Self {
+        ScanInput::File(path)
+    }
+}
+
+impl From<&Path> for ScanInput {
+    fn from(path: &Path) -> Self {
+        ScanInput::File(path.to_path_buf())
+    }
+}
+
+/// Outcome of scanning one of many inputs
+#[derive(Debug)]
+pub struct BatchScan {
+    pub result: Result<Vec<Rule>>,
+    /// Time spent scanning the input, including reading a file.
+    pub elapsed: Duration,
+}
+
+impl Rules {
+    /// Scan many buffers or files in parallel on the rayon thread pool
+    ///
+    /// A scanner is created for each batch of inputs rayon hands to a
+    /// thread and reused for all the inputs of the batch, rather than for
+    /// every input. Results are returned in the order of the inputs. Pools
+    /// with more threads than the 32 concurrent scans libyara allows are
+    /// replaced by a pool of 32 threads for the duration of the call.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use yara2::*;
+    /// let mut compiler = Compiler::new().unwrap();
+    /// compiler
+    ///     .add_rule_str(r#"rule flag { strings: $flag = "FLAG" condition: $flag }"#, None)
+    ///     .unwrap();
+    /// let rules = compiler.compile().unwrap();
+    ///
+    /// let inputs = vec![
+    ///     ScanInput::from(b"a FLAG".to_vec()),
+    ///     ScanInput::from(b"nothing".to_vec()),
+    ///     ScanInput::from(PathBuf::from("missing.bin")),
+    /// ];
+    /// let results = rules.scan_many(inputs, ScanOptions::default());
+    /// assert_eq!(results[0].result.as_ref().unwrap().len(), 1);
+    /// assert!(results[1].result.as_ref().unwrap().is_empty());
+    /// assert!(results[2].result.is_err());
+    /// ```
+    ///
+    /// # Arguments
+    /// `inputs` - buffers or paths of files to scan
+    /// `options` - settings for every scan
+    pub fn scan_many<I>(&self, inputs: I, options: ScanOptions) -> Vec<BatchScan>
+    where
+        I: IntoIterator,
+        I::Item: Into<ScanInput>,
+    {
+        let inputs = inputs
+            .into_iter()
+            .map(Into::into)
+            .collect::<Vec<ScanInput>>();
+        let scan = || {
+            inputs
+                .par_iter()
+                .map_init(
+                    || None,
+                    |scanner, input| {
+                        let start = Instant::now();
+                        let result = self
+                            .worker_scanner(options, scanner)
+                            .and_then(|scanner| input.scan(scanner));
+                        BatchScan {
+                            result,
+                            elapsed: start.elapsed(),
+                        }
+                    },
+                )
+                .collect()
+        };
+
+        if rayon::current_num_threads() <= MAX_SCAN_THREADS {
+            return scan();
+        }
+        match rayon::ThreadPoolBuilder::new()
+            .num_threads(MAX_SCAN_THREADS)
+            .build()
+        {
+            Ok(pool) => pool.install(scan),
+            Err(_) => scan(),
+        }
+    }
+}
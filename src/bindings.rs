@@ -1,6 +1,109 @@
 /* automatically generated by rust-bindgen 0.56.0 */
 
+pub const STRING_GFLAGS_REFERENCED: u32 = 1;
+pub const STRING_GFLAGS_HEXADECIMAL: u32 = 2;
+pub const STRING_GFLAGS_NO_CASE: u32 = 4;
+pub const STRING_GFLAGS_ASCII: u32 = 8;
+pub const STRING_GFLAGS_WIDE: u32 = 16;
+pub const STRING_GFLAGS_REGEXP: u32 = 32;
+pub const STRING_GFLAGS_FAST_REGEXP: u32 = 64;
+pub const STRING_GFLAGS_FULL_WORD: u32 = 128;
+pub const STRING_GFLAGS_ANONYMOUS: u32 = 256;
+pub const STRING_GFLAGS_SINGLE_MATCH: u32 = 512;
+pub const STRING_GFLAGS_LITERAL: u32 = 1024;
+pub const STRING_GFLAGS_FITS_IN_ATOM: u32 = 2048;
 pub const STRING_GFLAGS_NULL: u32 = 4096;
+pub const STRING_GFLAGS_CHAIN_PART: u32 = 8192;
+pub const STRING_GFLAGS_CHAIN_TAIL: u32 = 16384;
+pub const STRING_GFLAGS_FIXED_OFFSET: u32 = 32768;
+pub const STRING_GFLAGS_GREEDY_REGEXP: u32 = 65536;
+pub const STRING_GFLAGS_DOT_ALL: u32 = 131072;
+pub const STRING_GFLAGS_DISABLED: u32 = 262144;
+pub const STRING_GFLAGS_XOR: u32 = 524288;
+pub const ERROR_SUCCESS: u32 = 0;
+pub const ERROR_INSUFICIENT_MEMORY: u32 = 1;
+pub const ERROR_INSUFFICIENT_MEMORY: u32 = 1;
+pub const ERROR_COULD_NOT_ATTACH_TO_PROCESS: u32 = 2;
+pub const ERROR_COULD_NOT_OPEN_FILE: u32 = 3;
+pub const ERROR_COULD_NOT_MAP_FILE: u32 = 4;
+pub const ERROR_INVALID_FILE: u32 = 6;
+pub const ERROR_CORRUPT_FILE: u32 = 7;
+pub const ERROR_UNSUPPORTED_FILE_VERSION: u32 = 8;
+pub const ERROR_INVALID_REGULAR_EXPRESSION: u32 = 9;
+pub const ERROR_INVALID_HEX_STRING: u32 = 10;
+pub const ERROR_SYNTAX_ERROR: u32 = 11;
+pub const ERROR_LOOP_NESTING_LIMIT_EXCEEDED: u32 = 12;
+pub const ERROR_DUPLICATED_LOOP_IDENTIFIER: u32 = 13;
+pub const ERROR_DUPLICATED_IDENTIFIER: u32 = 14;
+pub const ERROR_DUPLICATED_TAG_IDENTIFIER: u32 = 15;
+pub const ERROR_DUPLICATED_META_IDENTIFIER: u32 = 16;
+pub const ERROR_DUPLICATED_STRING_IDENTIFIER: u32 = 17;
+pub const ERROR_UNREFERENCED_STRING: u32 = 18;
+pub const ERROR_UNDEFINED_STRING: u32 = 19;
+pub const ERROR_UNDEFINED_IDENTIFIER: u32 = 20;
+pub const ERROR_MISPLACED_ANONYMOUS_STRING: u32 = 21;
+pub const ERROR_INCLUDES_CIRCULAR_REFERENCE: u32 = 22;
+pub const ERROR_INCLUDE_DEPTH_EXCEEDED: u32 = 23;
+pub const ERROR_WRONG_TYPE: u32 = 24;
+pub const ERROR_EXEC_STACK_OVERFLOW: u32 = 25;
+pub const ERROR_SCAN_TIMEOUT: u32 = 26;
+pub const ERROR_TOO_MANY_SCAN_THREADS: u32 = 27;
+pub const ERROR_CALLBACK_ERROR: u32 = 28;
+pub const ERROR_INVALID_ARGUMENT: u32 = 29;
+pub const ERROR_TOO_MANY_MATCHES: u32 = 30;
+pub const ERROR_INTERNAL_FATAL_ERROR: u32 = 31;
+pub const ERROR_NESTED_FOR_OF_LOOP: u32 = 32;
+pub const ERROR_INVALID_FIELD_NAME: u32 = 33;
+pub const ERROR_UNKNOWN_MODULE: u32 = 34;
+pub const ERROR_NOT_A_STRUCTURE: u32 = 35;
+pub const ERROR_NOT_INDEXABLE: u32 = 36;
+pub const ERROR_NOT_A_FUNCTION: u32 = 37;
+pub const ERROR_INVALID_FORMAT: u32 = 38;
+pub const ERROR_TOO_MANY_ARGUMENTS: u32 = 39;
+pub const ERROR_WRONG_ARGUMENTS: u32 = 40;
+pub const ERROR_WRONG_RETURN_TYPE: u32 = 41;
+pub const ERROR_DUPLICATED_STRUCTURE_MEMBER: u32 = 42;
+pub const ERROR_EMPTY_STRING: u32 = 43;
+pub const ERROR_DIVISION_BY_ZERO: u32 = 44;
+pub const ERROR_REGULAR_EXPRESSION_TOO_LARGE: u32 = 45;
+pub const ERROR_TOO_MANY_RE_FIBERS: u32 = 46;
+pub const ERROR_COULD_NOT_READ_PROCESS_MEMORY: u32 = 47;
+pub const ERROR_INVALID_EXTERNAL_VARIABLE_TYPE: u32 = 48;
+pub const ERROR_REGULAR_EXPRESSION_TOO_COMPLEX: u32 = 49;
+pub const ERROR_INVALID_MODULE_NAME: u32 = 50;
+pub const ERROR_TOO_MANY_STRINGS: u32 = 51;
+pub const ERROR_INTEGER_OVERFLOW: u32 = 52;
+pub const ERROR_CALLBACK_REQUIRED: u32 = 53;
+pub const ERROR_INVALID_OPERAND: u32 = 54;
+pub const ERROR_COULD_NOT_READ_FILE: u32 = 55;
+pub const ERROR_DUPLICATED_EXTERNAL_VARIABLE: u32 = 56;
+pub const ERROR_INVALID_MODULE_DATA: u32 = 57;
+pub const ERROR_WRITING_FILE: u32 = 58;
+pub const YARA_ERROR_LEVEL_ERROR: u32 = 0;
+pub const YARA_ERROR_LEVEL_WARNING: u32 = 1;
+pub const OBJECT_TYPE_INTEGER: u32 = 1;
+pub const OBJECT_TYPE_STRING: u32 = 2;
+pub const OBJECT_TYPE_STRUCTURE: u32 = 3;
+pub const OBJECT_TYPE_ARRAY: u32 = 4;
+pub const OBJECT_TYPE_FUNCTION: u32 = 5;
+pub const OBJECT_TYPE_REGEXP: u32 = 6;
+pub const OBJECT_TYPE_DICTIONARY: u32 = 7;
+pub const OBJECT_TYPE_FLOAT: u32 = 8;
+pub const UNDEFINED: i64 = -1483400188077313;
+pub const RULE_TFLAGS_MATCH: u32 = 1;
+pub const RULE_GFLAGS_NULL: u32 = 4096;
+pub const RULE_GFLAGS_DISABLED: u32 = 8192;
+pub const CALLBACK_MSG_RULE_MATCHING: u32 = 1;
+pub const CALLBACK_MSG_RULE_NOT_MATCHING: u32 = 2;
+pub const CALLBACK_MSG_SCAN_FINISHED: u32 = 3;
+pub const CALLBACK_MSG_IMPORT_MODULE: u32 = 4;
+pub const CALLBACK_MSG_MODULE_IMPORTED: u32 = 5;
+pub const CALLBACK_CONTINUE: u32 = 0;
+pub const CALLBACK_ABORT: u32 = 1;
+pub const CALLBACK_ERROR: u32 = 2;
+pub const SCAN_FLAGS_FAST_MODE: u32 = 1;
+pub const SCAN_FLAGS_PROCESS_MEMORY: u32 = 2;
+pub const SCAN_FLAGS_NO_TRYCATCH: u32 = 4;
 pub const META_TYPE_NULL: u32 = 0;
 pub const META_TYPE_INTEGER: u32 = 1;
 pub const META_TYPE_STRING: u32 = 2;
@@ -410,6 +513,98 @@ pub struct YR_RULES {
     pub ac_tables_size: u32,
     pub time_cost: u64,
 }
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct SIZED_STRING {
+    pub length: u32,
+    pub flags: u32,
+    pub c_string: [::std::os::raw::c_char; 1usize],
+}
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union YR_VALUE {
+    pub i: i64,
+    pub d: f64,
+    pub p: *mut ::std::os::raw::c_void,
+    pub o: *mut YR_OBJECT,
+    pub s: *mut YR_STRING,
+    pub ss: *mut SIZED_STRING,
+    _bindgen_union_align: u64,
+}
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct YR_OBJECT {
+    pub canary: ::std::os::raw::c_int,
+    pub type_: i8,
+    pub identifier: *const ::std::os::raw::c_char,
+    pub parent: *mut YR_OBJECT,
+    pub data: *mut ::std::os::raw::c_void,
+    pub value: YR_VALUE,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct YR_STRUCTURE_MEMBER {
+    pub object: *mut YR_OBJECT,
+    pub next: *mut YR_STRUCTURE_MEMBER,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct YR_OBJECT_STRUCTURE {
+    pub canary: ::std::os::raw::c_int,
+    pub type_: i8,
+    pub identifier: *const ::std::os::raw::c_char,
+    pub parent: *mut YR_OBJECT,
+    pub data: *mut ::std::os::raw::c_void,
+    pub members: *mut YR_STRUCTURE_MEMBER,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct YR_ARRAY_ITEMS {
+    pub count: ::std::os::raw::c_int,
+    pub objects: [*mut YR_OBJECT; 1usize],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct YR_OBJECT_ARRAY {
+    pub canary: ::std::os::raw::c_int,
+    pub type_: i8,
+    pub identifier: *const ::std::os::raw::c_char,
+    pub parent: *mut YR_OBJECT,
+    pub data: *mut ::std::os::raw::c_void,
+    pub prototype_item: *mut YR_OBJECT,
+    pub items: *mut YR_ARRAY_ITEMS,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct YR_DICTIONARY_ITEMS {
+    pub used: ::std::os::raw::c_int,
+    pub free: ::std::os::raw::c_int,
+    pub objects: [YR_DICTIONARY_ITEMS__bindgen_ty_1; 1usize],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct YR_DICTIONARY_ITEMS__bindgen_ty_1 {
+    pub key: *mut ::std::os::raw::c_char,
+    pub obj: *mut YR_OBJECT,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct YR_OBJECT_DICTIONARY {
+    pub canary: ::std::os::raw::c_int,
+    pub type_: i8,
+    pub identifier: *const ::std::os::raw::c_char,
+    pub parent: *mut YR_OBJECT,
+    pub data: *mut ::std::os::raw::c_void,
+    pub prototype_item: *mut YR_OBJECT,
+    pub items: *mut YR_DICTIONARY_ITEMS,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct YR_MODULE_IMPORT {
+    pub module_name: *const ::std::os::raw::c_char,
+    pub module_data: *mut ::std::os::raw::c_void,
+    pub module_data_size: size_t,
+}
 pub type YR_CALLBACK_FUNC = ::std::option::Option<
     unsafe extern "C" fn(
         message: ::std::os::raw::c_int,
@@ -528,6 +723,80 @@ pub struct _YR_COMPILER {
     pub atoms_config: YR_ATOMS_CONFIG,
 }
 pub type YR_COMPILER = _YR_COMPILER;
+pub type YR_FILE_DESCRIPTOR = ::std::os::raw::c_int;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct YR_SCANNER {
+    _unused: [u8; 0],
+}
+pub type YR_SCAN_CONTEXT = YR_SCANNER;
+pub type YR_MODULE_FUNC = ::std::option::Option<
+    unsafe extern "C" fn(
+        __args: *mut YR_VALUE,
+        __context: *mut YR_SCAN_CONTEXT,
+        __function_obj: *mut YR_OBJECT_FUNCTION,
+    ) -> ::std::os::raw::c_int,
+>;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct YR_OBJECT_FUNCTION__bindgen_ty_1 {
+    pub arguments_fmt: *const ::std::os::raw::c_char,
+    pub code: YR_MODULE_FUNC,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct YR_OBJECT_FUNCTION {
+    pub canary: ::std::os::raw::c_int,
+    pub type_: i8,
+    pub identifier: *const ::std::os::raw::c_char,
+    pub parent: *mut YR_OBJECT,
+    pub data: *mut ::std::os::raw::c_void,
+    pub return_obj: *mut YR_OBJECT,
+    pub prototypes: [YR_OBJECT_FUNCTION__bindgen_ty_1; 10usize],
+}
+extern "C" {
+    pub fn yr_object_create(
+        type_: i8,
+        identifier: *const ::std::os::raw::c_char,
+        parent: *mut YR_OBJECT,
+        object: *mut *mut YR_OBJECT,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_object_function_create(
+        identifier: *const ::std::os::raw::c_char,
+        arguments_fmt: *const ::std::os::raw::c_char,
+        return_fmt: *const ::std::os::raw::c_char,
+        func: YR_MODULE_FUNC,
+        parent: *mut YR_OBJECT,
+        function: *mut *mut YR_OBJECT,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_object_set_integer(
+        value: i64,
+        object: *mut YR_OBJECT,
+        field: *const ::std::os::raw::c_char,
+        ...
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_object_set_float(
+        value: f64,
+        object: *mut YR_OBJECT,
+        field: *const ::std::os::raw::c_char,
+        ...
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_object_set_string(
+        value: *const ::std::os::raw::c_char,
+        len: size_t,
+        object: *mut YR_OBJECT,
+        field: *const ::std::os::raw::c_char,
+        ...
+    ) -> ::std::os::raw::c_int;
+}
 extern "C" {
     pub fn yr_compiler_create(compiler: *mut *mut YR_COMPILER) -> ::std::os::raw::c_int;
 }
@@ -548,6 +817,42 @@ extern "C" {
         namespace_: *const ::std::os::raw::c_char,
     ) -> ::std::os::raw::c_int;
 }
+extern "C" {
+    pub fn yr_compiler_add_fd(
+        compiler: *mut YR_COMPILER,
+        rules_fd: YR_FILE_DESCRIPTOR,
+        namespace_: *const ::std::os::raw::c_char,
+        file_name: *const ::std::os::raw::c_char,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_compiler_define_integer_variable(
+        compiler: *mut YR_COMPILER,
+        identifier: *const ::std::os::raw::c_char,
+        value: i64,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_compiler_define_boolean_variable(
+        compiler: *mut YR_COMPILER,
+        identifier: *const ::std::os::raw::c_char,
+        value: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_compiler_define_float_variable(
+        compiler: *mut YR_COMPILER,
+        identifier: *const ::std::os::raw::c_char,
+        value: f64,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_compiler_define_string_variable(
+        compiler: *mut YR_COMPILER,
+        identifier: *const ::std::os::raw::c_char,
+        value: *const ::std::os::raw::c_char,
+    ) -> ::std::os::raw::c_int;
+}
 extern "C" {
     pub fn yr_compiler_get_rules(
         compiler: *mut YR_COMPILER,
@@ -585,9 +890,158 @@ extern "C" {
         timeout: ::std::os::raw::c_int,
     ) -> ::std::os::raw::c_int;
 }
+pub type YR_STREAM_READ_FUNC = ::std::option::Option<
+    unsafe extern "C" fn(
+        ptr: *mut ::std::os::raw::c_void,
+        size: size_t,
+        count: size_t,
+        user_data: *mut ::std::os::raw::c_void,
+    ) -> size_t,
+>;
+pub type YR_STREAM_WRITE_FUNC = ::std::option::Option<
+    unsafe extern "C" fn(
+        ptr: *const ::std::os::raw::c_void,
+        size: size_t,
+        count: size_t,
+        user_data: *mut ::std::os::raw::c_void,
+    ) -> size_t,
+>;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct _YR_STREAM {
+    pub user_data: *mut ::std::os::raw::c_void,
+    pub read: YR_STREAM_READ_FUNC,
+    pub write: YR_STREAM_WRITE_FUNC,
+}
+pub type YR_STREAM = _YR_STREAM;
+extern "C" {
+    pub fn yr_rules_save(
+        rules: *mut YR_RULES,
+        filename: *const ::std::os::raw::c_char,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_rules_save_stream(
+        rules: *mut YR_RULES,
+        stream: *mut YR_STREAM,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_rules_load(
+        filename: *const ::std::os::raw::c_char,
+        rules: *mut *mut YR_RULES,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_rules_load_stream(
+        stream: *mut YR_STREAM,
+        rules: *mut *mut YR_RULES,
+    ) -> ::std::os::raw::c_int;
+}
 extern "C" {
     pub fn yr_rules_destroy(rules: *mut YR_RULES) -> ::std::os::raw::c_int;
 }
+extern "C" {
+    pub fn yr_rule_disable(rule: *mut YR_RULE);
+}
+extern "C" {
+    pub fn yr_rule_enable(rule: *mut YR_RULE);
+}
+extern "C" {
+    pub fn yr_scanner_create(
+        rules: *mut YR_RULES,
+        scanner: *mut *mut YR_SCANNER,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_scanner_destroy(scanner: *mut YR_SCANNER);
+}
+extern "C" {
+    pub fn yr_scanner_set_callback(
+        scanner: *mut YR_SCANNER,
+        callback: YR_CALLBACK_FUNC,
+        user_data: *mut ::std::os::raw::c_void,
+    );
+}
+extern "C" {
+    pub fn yr_scanner_set_timeout(scanner: *mut YR_SCANNER, timeout: ::std::os::raw::c_int);
+}
+extern "C" {
+    pub fn yr_scanner_set_flags(scanner: *mut YR_SCANNER, flags: ::std::os::raw::c_int);
+}
+extern "C" {
+    pub fn yr_scanner_define_integer_variable(
+        scanner: *mut YR_SCANNER,
+        identifier: *const ::std::os::raw::c_char,
+        value: i64,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_scanner_define_boolean_variable(
+        scanner: *mut YR_SCANNER,
+        identifier: *const ::std::os::raw::c_char,
+        value: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_scanner_define_float_variable(
+        scanner: *mut YR_SCANNER,
+        identifier: *const ::std::os::raw::c_char,
+        value: f64,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_scanner_define_string_variable(
+        scanner: *mut YR_SCANNER,
+        identifier: *const ::std::os::raw::c_char,
+        value: *const ::std::os::raw::c_char,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_scanner_scan_mem(
+        scanner: *mut YR_SCANNER,
+        buffer: *const u8,
+        buffer_size: size_t,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_scanner_scan_fd(
+        scanner: *mut YR_SCANNER,
+        fd: YR_FILE_DESCRIPTOR,
+    ) -> ::std::os::raw::c_int;
+}
+pub type YR_MEMORY_BLOCK_FETCH_DATA_FUNC =
+    ::std::option::Option<unsafe extern "C" fn(self_: *mut YR_MEMORY_BLOCK) -> *const u8>;
+pub type YR_MEMORY_BLOCK_ITERATOR_FUNC = ::std::option::Option<
+    unsafe extern "C" fn(self_: *mut YR_MEMORY_BLOCK_ITERATOR) -> *mut YR_MEMORY_BLOCK,
+>;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct YR_MEMORY_BLOCK {
+    pub size: size_t,
+    pub base: u64,
+    pub context: *mut ::std::os::raw::c_void,
+    pub fetch_data: YR_MEMORY_BLOCK_FETCH_DATA_FUNC,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct YR_MEMORY_BLOCK_ITERATOR {
+    pub context: *mut ::std::os::raw::c_void,
+    pub first: YR_MEMORY_BLOCK_ITERATOR_FUNC,
+    pub next: YR_MEMORY_BLOCK_ITERATOR_FUNC,
+}
+extern "C" {
+    pub fn yr_scanner_scan_mem_blocks(
+        scanner: *mut YR_SCANNER,
+        iterator: *mut YR_MEMORY_BLOCK_ITERATOR,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn yr_scanner_scan_proc(
+        scanner: *mut YR_SCANNER,
+        pid: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
 extern "C" {
     pub fn yr_initialize() -> ::std::os::raw::c_int;
 }
@@ -1,15 +1,162 @@
-use crate::Rule;
+use crate::bindings;
+use crate::{Module, Rule};
+use std::any::Any;
+use std::collections::HashMap;
+use std::ffi::CStr;
 use std::os::raw::{c_int, c_void};
+use std::panic::{catch_unwind, AssertUnwindSafe};
 
+/// Something that happened during a scan
+///
+/// Events are delivered in the order libyara reports them. Module events
+/// come first, then one event per rule, and finally
+/// [`ScanEvent::ScanFinished`].
+#[derive(Debug)]
+pub enum ScanEvent<'a> {
+    /// A rule matched the scanned data
+    RuleMatching(Rule),
+    /// A rule did not match the scanned data. Every rule not matching is
+    /// reported, which can be costly with large rule sets.
+    RuleNotMatching(Rule),
+    /// A module imported by the rules is about to be loaded
+    ImportModule(&'a str),
+    /// A module imported by the rules has been loaded, with the data it
+    /// built for the scanned data
+    ModuleImported(Module),
+    /// All the rules have been evaluated
+    ScanFinished,
+}
+
+/// What the scan should do after an event
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScanControl {
+    /// Keep scanning
+    Continue,
+    /// Stop the scan, which then returns successfully
+    Abort,
+}
+
+/// Data handed to modules when they are imported, by module name
+pub(crate) type ModuleData = HashMap<String, Vec<u8>>;
+
+/// Rules and modules collected by [`scan_callback`]
+pub(crate) struct Collector<'a> {
+    pub rules: Vec<Rule>,
+    pub modules: Vec<Module>,
+    pub report_non_matching: bool,
+    pub collect_modules: bool,
+    pub module_data: &'a ModuleData,
+}
+
+/// Closure receiving the events of [`event_callback`]
+pub(crate) struct EventHandler<'a, F> {
+    pub callback: F,
+    pub module_data: &'a ModuleData,
+    /// Payload of a panic of the closure, resumed once the scan returns
+    pub panic: Option<Box<dyn Any + Send>>,
+}
+
+/// Callback collecting the rules reported by a scan into a [`Collector`]
 pub extern "C" fn scan_callback(
     msg: c_int,
     message_data: *mut c_void,
     user_data: *mut c_void,
 ) -> i32 {
-    let rules = unsafe { &mut *(user_data as *mut Vec<Rule>) };
-    if msg == 1 {
-        let rule: crate::bindings::YR_RULE = unsafe { std::ptr::read(message_data as _) };
-        rules.push(Rule::from(&rule));
+    let collector = unsafe { &mut *(user_data as *mut Collector) };
+    match msg as u32 {
+        bindings::CALLBACK_MSG_IMPORT_MODULE => {
+            attach_module_data(message_data, collector.module_data);
+            return bindings::CALLBACK_CONTINUE as i32;
+        }
+        bindings::CALLBACK_MSG_RULE_MATCHING => {}
+        bindings::CALLBACK_MSG_RULE_NOT_MATCHING if collector.report_non_matching => {}
+        bindings::CALLBACK_MSG_MODULE_IMPORTED if collector.collect_modules => {
+            let module = Module::from_object(message_data as *const _);
+            collector.modules.push(module);
+            return bindings::CALLBACK_CONTINUE as i32;
+        }
+        _ => return bindings::CALLBACK_CONTINUE as i32,
+    }
+    let rule = unsafe { &*(message_data as *const bindings::YR_RULE) };
+    collector.rules.push(Rule::from(rule));
+    bindings::CALLBACK_CONTINUE as i32
+}
+
+/// Callback forwarding every message as a [`ScanEvent`] to a closure `F`
+pub extern "C" fn event_callback<F>(
+    msg: c_int,
+    message_data: *mut c_void,
+    user_data: *mut c_void,
+) -> i32
+where
+    F: FnMut(ScanEvent) -> ScanControl,
+{
+    let handler = unsafe { &mut *(user_data as *mut EventHandler<F>) };
+    // unwinding into libyara is undefined behaviour, the panic is stopped
+    // here and the scan aborted with an error
+    let control = catch_unwind(AssertUnwindSafe(|| {
+        handle_event(handler, msg, message_data)
+    }));
+    match control {
+        Ok(ScanControl::Continue) => bindings::CALLBACK_CONTINUE as i32,
+        Ok(ScanControl::Abort) => bindings::CALLBACK_ABORT as i32,
+        Err(payload) => {
+            handler.panic = Some(payload);
+            bindings::CALLBACK_ERROR as i32
+        }
+    }
+}
+
+/// Turn a message into a [`ScanEvent`] for the closure of `handler`
+fn handle_event<F>(
+    handler: &mut EventHandler<F>,
+    msg: c_int,
+    message_data: *mut c_void,
+) -> ScanControl
+where
+    F: FnMut(ScanEvent) -> ScanControl,
+{
+    let event = match msg as u32 {
+        bindings::CALLBACK_MSG_RULE_MATCHING => ScanEvent::RuleMatching(Rule::from(unsafe {
+            &*(message_data as *const bindings::YR_RULE)
+        })),
+        bindings::CALLBACK_MSG_RULE_NOT_MATCHING => {
+            ScanEvent::RuleNotMatching(Rule::from(unsafe {
+                &*(message_data as *const bindings::YR_RULE)
+            }))
+        }
+        bindings::CALLBACK_MSG_IMPORT_MODULE => {
+            attach_module_data(message_data, handler.module_data);
+            let import = unsafe { &*(message_data as *const bindings::YR_MODULE_IMPORT) };
+            ScanEvent::ImportModule(unsafe { c_str(import.module_name) })
+        }
+        bindings::CALLBACK_MSG_MODULE_IMPORTED => {
+            ScanEvent::ModuleImported(Module::from_object(message_data as *const _))
+        }
+        bindings::CALLBACK_MSG_SCAN_FINISHED => ScanEvent::ScanFinished,
+        _ => return ScanControl::Continue,
+    };
+    (handler.callback)(event)
+}
+
+/// Hand the data set for a module to libyara as it gets imported
+///
+/// The data stays owned by the scanner, which outlives the scan, and
+/// libyara only reads it while loading the module.
+fn attach_module_data(message_data: *mut c_void, module_data: &ModuleData) {
+    let import = unsafe { &mut *(message_data as *mut bindings::YR_MODULE_IMPORT) };
+    let name = unsafe { c_str(import.module_name) };
+    if let Some(data) = module_data.get(name) {
+        import.module_data = data.as_ptr() as *mut c_void;
+        import.module_data_size = data.len() as _;
+    }
+}
+
+/// Borrow a string owned by libyara for the duration of a callback
+unsafe fn c_str<'a>(pointer: *const std::os::raw::c_char) -> &'a str {
+    if pointer.is_null() {
+        ""
+    } else {
+        CStr::from_ptr(pointer).to_str().unwrap_or_default()
     }
-    0 // Callback Success
 }
@@ -0,0 +1,275 @@
+use crate::bindings;
+use crate::errors::Diagnostics;
+use crate::variable::VariableDefiners;
+use crate::yara::{finalize, initialize};
+use crate::{Diagnostic, Error, Result, Rules, VariableValue};
+use std::convert::AsRef;
+use std::ffi::CString;
+use std::fs::File;
+use std::os::raw::c_void;
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+#[cfg(windows)]
+use std::os::windows::io::AsRawHandle;
+use std::path::Path;
+use std::ptr;
+
+/// Builds a set of [`Rules`]
+///
+/// Rules are added to the compiler, which is then consumed by
+/// [`Compiler::compile`]. This makes it impossible to add rules once
+/// they have been compiled.
+///
+/// # Example
+///
+/// ```
+/// use yara2::*;
+/// let mut compiler = Compiler::new().unwrap();
+/// compiler.add_rule_str(r#"rule is_awesome {
+///  strings:
+///    $rust = "rust" nocase
+///
+///  condition:
+///    $rust
+///}"#, None).unwrap();
+/// let rules = compiler.compile().unwrap();
+/// let results = rules
+///     .scan_memory(b"some data to scan contains rust", ScanOptions::default())
+///     .unwrap();
+/// assert_eq!(results.len(), 1);
+/// ```
+pub struct Compiler {
+    inner: *mut bindings::YR_COMPILER,
+    /// Filled by the error callback, boxed so its address is stable.
+    diagnostics: Box<Diagnostics>,
+    /// Set once adding rules failed, libyara cannot compile them anymore.
+    failed: bool,
+}
+
+impl Drop for Compiler {
+    fn drop(&mut self) {
+        unsafe {
+            bindings::yr_compiler_destroy(self.inner);
+        }
+        finalize().unwrap();
+    }
+}
+
+// Compilers are not tied to the thread that created them and report their
+// errors to their own diagnostics, they can be moved between threads.
+unsafe impl Send for Compiler {}
+
+impl Compiler {
+    /// Create a new compiler, initializing the Yara library if needed
+    pub fn new() -> Result<Compiler> {
+        initialize()?;
+
+        let mut pointer: *mut bindings::YR_COMPILER = ptr::null_mut();
+        let result = Error::from_code(unsafe { bindings::yr_compiler_create(&mut pointer) });
+        if let Err(err) = result {
+            finalize()?;
+            return Err(err);
+        }
+
+        let mut diagnostics = Box::new(Diagnostics::default());
+        unsafe {
+            bindings::yr_compiler_set_callback(
+                pointer,
+                Some(crate::errors::error_callback),
+                &mut *diagnostics as *mut Diagnostics as *mut c_void,
+            );
+        }
+
+        Ok(Compiler {
+            inner: pointer,
+            diagnostics,
+            failed: false,
+        })
+    }
+
+    /// Declare an external variable, with its default value
+    ///
+    /// Variables must be declared before adding the rules using them,
+    /// rules referencing an undeclared variable fail to compile with an
+    /// `undefined identifier` error. The type of the value is the type of
+    /// the variable, scanners can override the value but not the type.
+    ///
+    /// # Arguments
+    /// `name` - identifier used in rule conditions
+    /// `value` - integer, float, boolean or string default value
+    ///
+    pub fn define_variable<V: Into<VariableValue>>(&mut self, name: &str, value: V) -> Result<()> {
+        VariableDefiners {
+            integer: bindings::yr_compiler_define_integer_variable,
+            float: bindings::yr_compiler_define_float_variable,
+            boolean: bindings::yr_compiler_define_boolean_variable,
+            string: bindings::yr_compiler_define_string_variable,
+        }
+        .define(self.inner, name, &value.into())
+    }
+
+    /// Add a rule to the compiler
+    ///
+    /// Once adding a rule failed, libyara cannot take any more rules and
+    /// this fails with [`Error::InvalidRule`].
+    ///
+    /// # Arguments
+    /// `rule` - valid yara rule
+    /// `namespace` - optional namespace to store yara results
+    ///
+    pub fn add_rule_str(&mut self, rule: &str, namespace: Option<&str>) -> Result<()> {
+        self.check_not_failed()?;
+        let c_rule = CString::new(rule).map_err(|_| Error::InvalidRule)?;
+        let c_namespace = namespace.map(|ns| CString::new(ns).unwrap_or_default());
+
+        let count = unsafe {
+            bindings::yr_compiler_add_string(
+                self.inner,
+                c_rule.as_ptr(),
+                c_namespace
+                    .as_ref()
+                    .map_or_else(ptr::null, |ns| ns.as_ptr()),
+            )
+        };
+        match count {
+            0 => Ok(()),
+            _ => Err(Error::Multiple(self.compile_errors())),
+        }
+    }
+
+    /// Add the rules contained in a file to the compiler
+    ///
+    /// Like [`add_rule_str`](Compiler::add_rule_str), fails with
+    /// [`Error::InvalidRule`] once adding a rule failed.
+    ///
+    /// # Arguments
+    /// `path` - path to a file containing yara rules
+    /// `namespace` - optional namespace to store yara results
+    ///
+    pub fn add_rule_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        namespace: Option<&str>,
+    ) -> Result<()> {
+        self.check_not_failed()?;
+        let path = path.as_ref();
+        let name = path.to_string_lossy().to_string();
+        let file = File::open(path).map_err(|_| Error::InvalidFile(name.clone()))?;
+        let c_name = CString::new(name.as_str()).unwrap_or_default();
+        let c_namespace = namespace.map(|ns| CString::new(ns).unwrap_or_default());
+
+        let count = self.add_raw(
+            &file,
+            c_namespace
+                .as_ref()
+                .map_or_else(ptr::null, |ns| ns.as_ptr()),
+            c_name.as_ptr(),
+        );
+        match count {
+            0 => Ok(()),
+            _ => Err(Error::InvalidRuleFile(name, self.compile_errors())),
+        }
+    }
+
+    /// Recursively add all rule files found under a directory
+    ///
+    /// Files are added in lexical order and only those whose file name
+    /// matches `pattern` (e.g. `"*.yar"`) are considered. Adding stops at
+    /// the first file that fails to compile, and the returned
+    /// [`Error::InvalidRuleFile`] names that file with its diagnostics:
+    /// libyara cannot take any more rules after a failure, so the remaining
+    /// files are not tried. Errors reading the directory are reported as
+    /// [`Error::Io`].
+    ///
+    /// Returns the number of files added.
+    ///
+    /// # Arguments
+    /// `path` - directory to walk
+    /// `pattern` - glob applied to the file name of every file found
+    /// `namespace` - optional namespace to store yara results
+    ///
+    pub fn add_rules_dir<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        pattern: &str,
+        namespace: Option<&str>,
+    ) -> Result<usize> {
+        self.check_not_failed()?;
+        let pattern =
+            glob::Pattern::new(pattern).map_err(|_| Error::InvalidPattern(pattern.to_string()))?;
+        let mut count = 0;
+
+        for entry in walkdir::WalkDir::new(&path).sort_by(|a, b| a.file_name().cmp(b.file_name())) {
+            let entry = entry.map_err(|err| Error::Io(std::io::Error::from(err)))?;
+
+            if entry.file_type().is_file() && pattern.matches(&entry.file_name().to_string_lossy())
+            {
+                self.add_rule_file(entry.path(), namespace)?;
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Warnings reported for the rules added so far
+    ///
+    /// Warnings do not prevent rules from compiling, they are kept here
+    /// rather than reported as errors, and handed over to the compiled
+    /// rules, see [`Rules::warnings`].
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.diagnostics.warnings
+    }
+
+    /// Compile the rules added so far
+    ///
+    /// The compiler is consumed, the resulting [`Rules`] are immutable
+    /// and can be used for as many scans as needed. Fails with
+    /// [`Error::InvalidRule`] if adding any of the rules failed.
+    pub fn compile(mut self) -> Result<Rules> {
+        self.check_not_failed()?;
+        let mut pointer = ptr::null_mut();
+        Error::from_code(unsafe { bindings::yr_compiler_get_rules(self.inner, &mut pointer) })?;
+        let warnings = std::mem::take(&mut self.diagnostics.warnings);
+        Rules::new(pointer).map(|rules| rules.with_warnings(warnings))
+    }
+
+    /// Fails once adding rules failed, libyara asserts it has no errors
+    /// when adding or compiling rules, which would abort the process
+    fn check_not_failed(&self) -> Result<()> {
+        if self.failed {
+            Err(Error::InvalidRule)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Drains the errors reported by the error callback for a failed
+    /// compilation, which also leaves the compiler unable to compile
+    fn compile_errors(&mut self) -> Vec<Diagnostic> {
+        self.failed = true;
+        std::mem::take(&mut self.diagnostics.errors)
+    }
+
+    #[cfg(unix)]
+    fn add_raw(
+        &self,
+        file: &File,
+        namespace: *const std::os::raw::c_char,
+        file_name: *const std::os::raw::c_char,
+    ) -> i32 {
+        let fd = file.as_raw_fd();
+        unsafe { bindings::yr_compiler_add_fd(self.inner, fd, namespace, file_name) }
+    }
+
+    #[cfg(windows)]
+    fn add_raw(
+        &self,
+        file: &File,
+        namespace: *const std::os::raw::c_char,
+        file_name: *const std::os::raw::c_char,
+    ) -> i32 {
+        let handle = file.as_raw_handle();
+        unsafe { bindings::yr_compiler_add_fd(self.inner, handle as _, namespace, file_name) }
+    }
+}
@@ -0,0 +1,258 @@
+use crate::rules::MAX_SCAN_THREADS;
+use crate::{Error, Result, Rule, Rules, ScanOptions};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Result of scanning one file of a directory
+pub type FileScan = (PathBuf, Result<Vec<Rule>>);
+
+/// Scans all the files under a directory on a pool of threads
+///
+/// Glob patterns containing a `/` are matched against the path relative
+/// to the scanned directory, others against the file name only. Excluded
+/// directories are not walked at all.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use yara2::*;
+/// let mut compiler = Compiler::new().unwrap();
+/// compiler.add_rule_str("rule always { condition: true }", None).unwrap();
+/// let rules = Arc::new(compiler.compile().unwrap());
+///
+/// let mut scanner = DirectoryScanner::new(rules);
+/// scanner
+///     .exclude("*.iso")
+///     .unwrap()
+///     .exclude("node_modules")
+///     .unwrap()
+///     .set_max_file_size(Some(64 * 1024 * 1024))
+///     .set_threads(8);
+/// for (path, result) in scanner.scan("/home") {
+///     println!("{}: {:?}", path.display(), result);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct DirectoryScanner {
+    rules: Arc<Rules>,
+    options: ScanOptions,
+    include: Vec<glob::Pattern>,
+    exclude: Vec<glob::Pattern>,
+    max_file_size: Option<u64>,
+    follow_symlinks: bool,
+    same_file_system: bool,
+    threads: usize,
+}
+
+impl DirectoryScanner {
+    /// Create a directory scanner for the given rules
+    ///
+    /// By default all the files are scanned, symbolic links are not
+    /// followed and there is one thread per CPU.
+    pub fn new(rules: Arc<Rules>) -> DirectoryScanner {
+        let threads = num_cpus::get();
+        DirectoryScanner {
+            rules,
+            options: ScanOptions::default(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            max_file_size: None,
+            follow_symlinks: false,
+            same_file_system: false,
+            threads: threads.min(MAX_SCAN_THREADS),
+        }
+    }
+
+    /// Set the options used to scan each file
+    pub fn set_options(&mut self, options: ScanOptions) -> &mut Self {
+        self.options = options;
+        self
+    }
+
+    /// Only scan files matching `pattern`, or any of the patterns when
+    /// called more than once
+    pub fn include(&mut self, pattern: &str) -> Result<&mut Self> {
+        self.include.push(Self::pattern(pattern)?);
+        Ok(self)
+    }
+
+    /// Skip files and directories matching `pattern`
+    pub fn exclude(&mut self, pattern: &str) -> Result<&mut Self> {
+        self.exclude.push(Self::pattern(pattern)?);
+        Ok(self)
+    }
+
+    /// Skip files larger than `max_file_size` bytes
+    pub fn set_max_file_size(&mut self, max_file_size: Option<u64>) -> &mut Self {
+        self.max_file_size = max_file_size;
+        self
+    }
+
+    /// Follow symbolic links to files and directories
+    pub fn set_follow_symlinks(&mut self, follow_symlinks: bool) -> &mut Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Do not cross into other file systems, such as network mounts
+    pub fn set_same_file_system(&mut self, same_file_system: bool) -> &mut Self {
+        self.same_file_system = same_file_system;
+        self
+    }
+
+    /// Number of files scanned at the same time
+    ///
+    /// libyara allows at most 32 concurrent scans of the same rules, the
+    /// number of threads is capped accordingly.
+    pub fn set_threads(&mut self, threads: usize) -> &mut Self {
+        self.threads = threads.clamp(1, MAX_SCAN_THREADS);
+        self
+    }
+
+    /// Start scanning the files under `path`
+    ///
+    /// Files are scanned in the background, the results are returned as
+    /// they come, in no particular order. Errors while walking the
+    /// directory are reported as [`Error::Io`] for the path that could not
+    /// be read, symbolic link loops with an error of kind `Other`.
+    /// Dropping the returned [`DirectoryScan`] stops the scan.
+    ///
+    /// # Arguments
+    /// `path` - directory to scan
+    pub fn scan<P: AsRef<Path>>(&self, path: P) -> DirectoryScan {
+        let root = path.as_ref().to_path_buf();
+        let (paths, queue) = sync_channel::<PathBuf>(self.threads * 4);
+        let (results, receiver) = sync_channel::<FileScan>(self.threads * 4);
+        let queue = Arc::new(Mutex::new(queue));
+
+        let mut workers = (0..self.threads)
+            .map(|_| {
+                let rules = self.rules.clone();
+                let options = self.options;
+                let queue = queue.clone();
+                let results = results.clone();
+                thread::spawn(move || Self::work(&rules, options, &queue, &results))
+            })
+            .collect::<Vec<_>>();
+
+        let walker = self.clone();
+        workers.push(thread::spawn(move || walker.walk(&root, &paths, &results)));
+
+        DirectoryScan { receiver, workers }
+    }
+
+    /// Send the files to scan to the workers, and walk errors to the results
+    fn walk(&self, root: &Path, paths: &SyncSender<PathBuf>, results: &SyncSender<FileScan>) {
+        let entries = walkdir::WalkDir::new(root)
+            .follow_links(self.follow_symlinks)
+            .same_file_system(self.same_file_system)
+            .into_iter()
+            .filter_entry(|entry| entry.depth() == 0 || !self.excluded(root, entry.path()));
+
+        for entry in entries {
+            let sent = match entry {
+                Ok(entry) if entry.file_type().is_file() && self.wanted(root, &entry) => {
+                    paths.send(entry.into_path()).is_ok()
+                }
+                Ok(_) => true,
+                Err(err) => {
+                    let path = err.path().unwrap_or(root).to_path_buf();
+                    let err = Error::Io(std::io::Error::from(err));
+                    results.send((path, Err(err))).is_ok()
+                }
+            };
+            if !sent {
+                break;
+            }
+        }
+    }
+
+    fn work(
+        rules: &Rules,
+        options: ScanOptions,
+        queue: &Mutex<Receiver<PathBuf>>,
+        results: &SyncSender<FileScan>,
+    ) {
+        let mut scanner = None;
+        loop {
+            let path = match queue.lock().unwrap().recv() {
+                Ok(path) => path,
+                Err(_) => break,
+            };
+            let result = rules
+                .worker_scanner(options, &mut scanner)
+                .and_then(|scanner| scanner.scan_file(&path));
+            if results.send((path, result)).is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Whether a file passes the include patterns and the size limit
+    fn wanted(&self, root: &Path, entry: &walkdir::DirEntry) -> bool {
+        let included = self.include.is_empty() || Self::matches(&self.include, root, entry.path());
+        let small_enough = match self.max_file_size {
+            Some(max) => entry.metadata().map(|m| m.len() <= max).unwrap_or(false),
+            None => true,
+        };
+        included && small_enough
+    }
+
+    fn excluded(&self, root: &Path, path: &Path) -> bool {
+        Self::matches(&self.exclude, root, path)
+    }
+
+    fn matches(patterns: &[glob::Pattern], root: &Path, path: &Path) -> bool {
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        let name = path.file_name().map(Path::new).unwrap_or(path);
+        patterns.iter().any(|pattern| {
+            if pattern.as_str().contains('/') {
+                pattern.matches_path(relative)
+            } else {
+                pattern.matches_path(name)
+            }
+        })
+    }
+
+    fn pattern(pattern: &str) -> Result<glob::Pattern> {
+        glob::Pattern::new(pattern).map_err(|_| Error::InvalidPattern(pattern.to_string()))
+    }
+}
+
+/// Results of a [`DirectoryScanner`], as they come
+///
+/// Iterating blocks until the next file is scanned and ends once all the
+/// files have been scanned.
+pub struct DirectoryScan {
+    receiver: Receiver<FileScan>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl DirectoryScan {
+    /// Channel receiving the results, for use with other threads
+    ///
+    /// The scan keeps running in the background until all the files are
+    /// scanned or the receiver is dropped.
+    pub fn into_receiver(self) -> Receiver<FileScan> {
+        self.receiver
+    }
+}
+
+impl Iterator for DirectoryScan {
+    type Item = FileScan;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.receiver.recv() {
+            Ok(result) => Some(result),
+            Err(_) => {
+                for worker in self.workers.drain(..) {
+                    let _ = worker.join();
+                }
+                None
+            }
+        }
+    }
+}
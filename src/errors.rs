@@ -1,12 +1,6 @@
-use std::{
-    collections::VecDeque,
-    os::raw::c_void,
-    sync::{Arc, Mutex},
-};
-
-lazy_static! {
-    pub static ref LAST_ERROR: Arc<Mutex<VecDeque<String>>> = Arc::new(Mutex::new(VecDeque::new()));
-}
+use crate::bindings;
+use serde::Serialize;
+use std::{fmt, os::raw::c_void};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -14,18 +8,76 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub enum Error {
     #[error(display = "Rules have already been compiled")]
     AlreadyCompiled,
+    #[error(display = "No compiled rules to scan with")]
+    NoRules,
     #[error(display = "Callback error")]
     CallbackError,
     #[error(display = "Invalid rule syntax")]
     InvalidRule,
     #[error(display = "Too many rules")]
     TooManyRules,
+    #[error(display = "Scan timed out")]
+    Timeout,
+    #[error(display = "Too many threads scanning at the same time")]
+    TooManyScanThreads,
+    #[error(display = "Scan cancelled")]
+    Cancelled,
+    #[error(display = "Cannot attach to process")]
+    CouldNotAttachToProcess,
     #[error(display = "Cannot open file: {}", _0)]
     InvalidFile(String),
+    #[error(display = "Cannot compile rules in {}", _0)]
+    InvalidRuleFile(String, Vec<Diagnostic>),
+    #[error(display = "Invalid glob pattern: {}", _0)]
+    InvalidPattern(String),
+    #[error(display = "Invalid external variable: {}", _0)]
+    InvalidVariable(String),
+    #[error(display = "Undefined external variable: {}", _0)]
+    UndefinedVariable(String),
+    #[error(display = "Wrong type for external variable: {}", _0)]
+    InvalidVariableType(String),
+    #[error(display = "No rule named {}", _0)]
+    UnknownRule(String),
+    #[error(display = "Module not built into libyara: {}", _0)]
+    UnknownModule(String),
+    #[error(display = "Invalid module declaration: {}", _0)]
+    InvalidDeclaration(String),
+    #[error(display = "Data larger than {} bytes", _0)]
+    DataTooLarge(usize),
+    #[error(display = "I/O error: {}", _0)]
+    Io(#[error(source)] std::io::Error),
     #[error(display = "Unknown yara error: {}", _0)]
     UnknownYaraError(i32),
     #[error(display = "Multiple yara errors")]
-    Multiple(Vec<String>),
+    Multiple(Vec<Diagnostic>),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A message reported by the compiler about a rule
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Diagnostic {
+    /// Whether the rule failed to compile or only deserves attention.
+    pub severity: Severity,
+    /// File containing the rule, when it was loaded from disk.
+    pub file: Option<String>,
+    /// Line of the offending rule.
+    pub line: i32,
+    /// Message from the compiler.
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.file {
+            Some(ref file) => write!(f, "{}:{}: {}", file, self.line, self.message),
+            None => write!(f, "Line {}: {}", self.line, self.message),
+        }
+    }
 }
 
 impl From<i32> for Error {
@@ -36,35 +88,55 @@ impl From<i32> for Error {
 
 impl Error {
     pub fn from_code(code: i32) -> Result<()> {
-        match code {
-            0 => Ok(()),
+        match code as u32 {
+            bindings::ERROR_SUCCESS => Ok(()),
+            bindings::ERROR_SCAN_TIMEOUT => Err(Error::Timeout),
+            bindings::ERROR_TOO_MANY_SCAN_THREADS => Err(Error::TooManyScanThreads),
+            bindings::ERROR_COULD_NOT_ATTACH_TO_PROCESS => Err(Error::CouldNotAttachToProcess),
             _ => Err(Error::from(code)),
         }
     }
 }
 
+/// Messages reported by libyara for a single compiler
+#[derive(Default)]
+pub(crate) struct Diagnostics {
+    pub errors: Vec<Diagnostic>,
+    pub warnings: Vec<Diagnostic>,
+}
+
 /// Callback reached when something goes wrong
 ///
 /// # Safety
-/// This function converts string pointers and uses unsafe code
-pub unsafe extern "C" fn error_callback(
-    _level: i32,
-    _file_name: *const i8,
-    _line_number: i32,
-    _message: *const i8,
-    _user_data: *mut c_void,
+/// `user_data` must point to the [`Diagnostics`] of the compiler
+/// reporting the message.
+pub(crate) unsafe extern "C" fn error_callback(
+    level: i32,
+    file_name: *const i8,
+    line_number: i32,
+    message: *const i8,
+    user_data: *mut c_void,
 ) {
     use std::ffi::CStr;
-    let message = CStr::from_ptr(_message)
+    let diagnostics = &mut *(user_data as *mut Diagnostics);
+    let message = CStr::from_ptr(message)
         .to_str()
         .map(|s| s.to_string())
         .unwrap_or_default();
+    let file = if file_name.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(file_name).to_string_lossy().to_string())
+    };
 
-    (LAST_ERROR.lock().unwrap()).push_back(format!("Line {}: {}", _line_number, message));
-}
-
-/// Retrieves the last error message from Yara
-pub fn get_last_error() -> Option<String> {
-    let mut lock = LAST_ERROR.lock().unwrap();
-    lock.pop_front()
+    let (severity, queue) = match level as u32 {
+        bindings::YARA_ERROR_LEVEL_WARNING => (Severity::Warning, &mut diagnostics.warnings),
+        _ => (Severity::Error, &mut diagnostics.errors),
+    };
+    queue.push(Diagnostic {
+        severity,
+        file,
+        line: line_number,
+        message,
+    });
 }
@@ -1,18 +1,59 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 
+#[macro_use]
+extern crate bitflags;
 #[macro_use]
 extern crate lazy_static;
 extern crate err_derive;
+extern crate glob;
+extern crate num_cpus;
+#[cfg(feature = "rayon")]
+extern crate rayon;
 extern crate serde;
+#[cfg(feature = "async")]
+extern crate tokio;
+extern crate walkdir;
 
+#[cfg(feature = "async")]
+mod asynchronous;
+#[cfg(feature = "rayon")]
+mod batch;
+#[allow(dead_code)]
 mod bindings;
 mod callbacks;
+mod compiler;
+mod directory;
 mod errors;
 mod libyara;
+mod memory_blocks;
+mod module;
+mod process;
+#[cfg(feature = "profiling")]
+mod profiling;
 mod rule;
+mod rules;
+mod scanner;
+mod variable;
 mod yara;
 
+#[cfg(feature = "async")]
+pub use asynchronous::*;
+#[cfg(feature = "rayon")]
+pub use batch::*;
+pub use callbacks::{ScanControl, ScanEvent};
+pub use compiler::*;
+pub use directory::*;
 pub use errors::*;
+pub use memory_blocks::{
+    MemoryBlock, MemoryBlockIterator, READER_BLOCK_OVERLAP, READER_BLOCK_SIZE,
+};
+pub use module::*;
+pub use process::*;
+#[cfg(feature = "profiling")]
+pub use profiling::*;
 pub use rule::*;
+pub use rules::*;
+pub use scanner::*;
+pub use variable::VariableValue;
 pub use yara::*;
@@ -0,0 +1,246 @@
+use crate::bindings;
+use std::io::{Read, Seek, SeekFrom};
+use std::os::raw::c_void;
+use std::ptr;
+
+/// Default size of the blocks read by [`Scanner::scan_reader`](crate::Scanner::scan_reader)
+pub const READER_BLOCK_SIZE: usize = 16 * 1024 * 1024;
+
+/// Default number of bytes shared by consecutive blocks read by
+/// [`Scanner::scan_reader`](crate::Scanner::scan_reader)
+pub const READER_BLOCK_OVERLAP: usize = 64 * 1024;
+
+/// A block of data to scan, starting at the address `base`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MemoryBlock {
+    /// Address of the first byte of the block, added to the offsets of
+    /// the matches found in it.
+    pub base: u64,
+    /// Number of bytes in the block.
+    pub size: usize,
+}
+
+/// Source of discontiguous blocks of data, scanned as a whole
+///
+/// Blocks are listed first and their data is only fetched when libyara
+/// gets to them, so a source such as a process or a reassembled network
+/// stream never needs to hold all its data in memory. The offset of a
+/// match is its address, the `base` of its block plus its position in the
+/// block, and conditions such as
+/// `entrypoint` work on the blocks as they would on process memory when
+/// [`ScanOptions::process_memory`](crate::ScanOptions::process_memory) is set.
+///
+/// Strings spanning two blocks are not found.
+///
+/// # Example
+///
+/// ```
+/// use yara2::*;
+///
+/// struct Chunks {
+///     chunks: Vec<(u64, Vec<u8>)>,
+///     current: usize,
+/// }
+///
+/// impl MemoryBlockIterator for Chunks {
+///     fn first(&mut self) -> Option<MemoryBlock> {
+///         self.current = 0;
+///         self.next()
+///     }
+///
+///     fn next(&mut self) -> Option<MemoryBlock> {
+///         let (base, data) = self.chunks.get(self.current)?;
+///         self.current += 1;
+///         Some(MemoryBlock { base: *base, size: data.len() })
+///     }
+///
+///     fn fetch(&mut self, block: &MemoryBlock) -> Option<&[u8]> {
+///         self.chunks
+///             .iter()
+///             .find(|(base, _)| *base == block.base)
+///             .map(|(_, data)| data.as_slice())
+///     }
+/// }
+///
+/// let mut compiler = Compiler::new().unwrap();
+/// compiler
+///     .add_rule_str(r#"rule flag { strings: $flag = "FLAG" condition: $flag }"#, None)
+///     .unwrap();
+/// let rules = compiler.compile().unwrap();
+///
+/// let mut chunks = Chunks {
+///     chunks: vec![(0x1000, b"nothing".to_vec()), (0x8000, b"a FLAG".to_vec())],
+///     current: 0,
+/// };
+/// let results = rules.scanner().unwrap().scan_blocks(&mut chunks).unwrap();
+/// assert_eq!(results[0].strings[0].matches[0].offset, 0x8002);
+/// ```
+pub trait MemoryBlockIterator {
+    /// Start over, returning the first block
+    fn first(&mut self) -> Option<MemoryBlock>;
+
+    /// Block following the last one returned
+    fn next(&mut self) -> Option<MemoryBlock>;
+
+    /// Data of the last block returned, `None` skips the block
+    ///
+    /// The data only needs to stay valid until the next call to any method
+    /// of the iterator. Returning less than `block.size` bytes shrinks the
+    /// block.
+    fn fetch(&mut self, block: &MemoryBlock) -> Option<&[u8]>;
+}
+
+/// Blocks read from a seekable reader, one block in memory at a time
+///
+/// Blocks start at the position of the reader and overlap by `overlap`
+/// bytes, so strings spanning the end of a block are found in the next
+/// one. libyara drops the matches found twice, they have the same address.
+pub(crate) struct ReaderBlocks<R> {
+    reader: R,
+    /// Position of the reader when the scan started, the offset 0.
+    start: u64,
+    size: u64,
+    block_size: usize,
+    overlap: usize,
+    next_base: u64,
+    buffer: Vec<u8>,
+    pub error: Option<std::io::Error>,
+}
+
+impl<R: Read + Seek> ReaderBlocks<R> {
+    // `stream_position` is too recent for the versions of rust supported
+    #[allow(clippy::seek_from_current)]
+    pub fn new(mut reader: R, block_size: usize, overlap: usize) -> std::io::Result<Self> {
+        let start = reader.seek(SeekFrom::Current(0))?;
+        let end = reader.seek(SeekFrom::End(0))?;
+        let block_size = block_size.max(1);
+        Ok(ReaderBlocks {
+            reader,
+            start,
+            size: end.saturating_sub(start),
+            block_size,
+            overlap: overlap.min(block_size / 2),
+            next_base: 0,
+            buffer: Vec::new(),
+            error: None,
+        })
+    }
+}
+
+impl<R: Read + Seek> MemoryBlockIterator for ReaderBlocks<R> {
+    fn first(&mut self) -> Option<MemoryBlock> {
+        self.next_base = 0;
+        self.next()
+    }
+
+    fn next(&mut self) -> Option<MemoryBlock> {
+        if self.next_base >= self.size {
+            return None;
+        }
+        let base = self.next_base;
+        let size = (self.size - base).min(self.block_size as u64) as usize;
+        self.next_base = if base + size as u64 >= self.size {
+            self.size
+        } else {
+            base + (size - self.overlap) as u64
+        };
+        Some(MemoryBlock { base, size })
+    }
+
+    fn fetch(&mut self, block: &MemoryBlock) -> Option<&[u8]> {
+        self.buffer.resize(block.size, 0);
+        let result = self
+            .reader
+            .seek(SeekFrom::Start(self.start + block.base))
+            .and_then(|_| self.reader.read_exact(&mut self.buffer));
+        match result {
+            Ok(_) => Some(&self.buffer),
+            Err(err) => {
+                self.error.get_or_insert(err);
+                None
+            }
+        }
+    }
+}
+
+/// Glue between a `YR_MEMORY_BLOCK_ITERATOR` and a [`MemoryBlockIterator`]
+///
+/// libyara keeps pointers to the current block, which lives here until the
+/// next call to `first` or `next`.
+pub(crate) struct Blocks<'a, I: ?Sized> {
+    iterator: &'a mut I,
+    current: bindings::YR_MEMORY_BLOCK,
+}
+
+impl<'a, I: MemoryBlockIterator + ?Sized> Blocks<'a, I> {
+    pub fn new(iterator: &'a mut I) -> Self {
+        Blocks {
+            iterator,
+            current: bindings::YR_MEMORY_BLOCK {
+                size: 0,
+                base: 0,
+                context: ptr::null_mut(),
+                fetch_data: None,
+            },
+        }
+    }
+
+    /// The libyara iterator over these blocks, valid as long as `self`
+    /// does not move
+    pub fn yr_iterator(&mut self) -> bindings::YR_MEMORY_BLOCK_ITERATOR {
+        bindings::YR_MEMORY_BLOCK_ITERATOR {
+            context: self as *mut Self as *mut c_void,
+            first: Some(iterator_first::<I>),
+            next: Some(iterator_next::<I>),
+        }
+    }
+
+    fn current(&mut self, block: Option<MemoryBlock>) -> *mut bindings::YR_MEMORY_BLOCK {
+        match block {
+            Some(block) => {
+                self.current = bindings::YR_MEMORY_BLOCK {
+                    size: block.size as _,
+                    base: block.base,
+                    context: self as *mut Self as *mut c_void,
+                    fetch_data: Some(fetch_data::<I>),
+                };
+                &mut self.current
+            }
+            None => ptr::null_mut(),
+        }
+    }
+}
+
+unsafe extern "C" fn iterator_first<I: MemoryBlockIterator + ?Sized>(
+    iterator: *mut bindings::YR_MEMORY_BLOCK_ITERATOR,
+) -> *mut bindings::YR_MEMORY_BLOCK {
+    let blocks = &mut *((*iterator).context as *mut Blocks<I>);
+    let block = blocks.iterator.first();
+    blocks.current(block)
+}
+
+unsafe extern "C" fn iterator_next<I: MemoryBlockIterator + ?Sized>(
+    iterator: *mut bindings::YR_MEMORY_BLOCK_ITERATOR,
+) -> *mut bindings::YR_MEMORY_BLOCK {
+    let blocks = &mut *((*iterator).context as *mut Blocks<I>);
+    let block = blocks.iterator.next();
+    blocks.current(block)
+}
+
+/// Fetch the data of a block, shrinking the block if less data is returned
+unsafe extern "C" fn fetch_data<I: MemoryBlockIterator + ?Sized>(
+    block: *mut bindings::YR_MEMORY_BLOCK,
+) -> *const u8 {
+    let blocks = &mut *((*block).context as *mut Blocks<I>);
+    let wanted = MemoryBlock {
+        base: (*block).base,
+        size: (*block).size as usize,
+    };
+    match blocks.iterator.fetch(&wanted) {
+        Some(data) => {
+            (*block).size = data.len().min(wanted.size) as _;
+            data.as_ptr()
+        }
+        None => ptr::null(),
+    }
+}
@@ -0,0 +1,477 @@
+use crate::bindings;
+use crate::{Error, ModuleValue, Result};
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_void};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::sync::{Arc, RwLock};
+
+/// Names of the modules written in rust built into libyara, from the
+/// `YARA2_RUST_MODULES` environment variable at build time
+const BUILT_MODULES: &str = env!("YARA2_RUST_MODULES");
+
+lazy_static! {
+    static ref MODULES: RwLock<HashMap<String, Arc<Registered>>> = RwLock::new(HashMap::new());
+}
+
+/// Type of a function argument or return value
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ValueType {
+    Integer,
+    Float,
+    String,
+}
+
+impl ValueType {
+    /// Character used by libyara for this type in function formats
+    fn format(self) -> char {
+        match self {
+            ValueType::Integer => 'i',
+            ValueType::Float => 'f',
+            ValueType::String => 's',
+        }
+    }
+}
+
+/// Function callable from rule conditions
+///
+/// Arguments are passed in declaration order. A value not matching the
+/// declared return type is returned as undefined.
+pub type ModuleFunction = fn(&[ModuleValue]) -> ModuleValue;
+
+/// Field of the data built by a [`CustomModule`]
+#[derive(Clone, Debug)]
+pub enum Declaration {
+    Integer(&'static str),
+    Float(&'static str),
+    String(&'static str),
+    Structure(&'static str, Vec<Declaration>),
+    /// Array of items, the name of the item declaration is not used.
+    Array(&'static str, Box<Declaration>),
+    /// Dictionary of items, the name of the item declaration is not used.
+    Dictionary(&'static str, Box<Declaration>),
+    /// Function, only one function may be declared with a given name.
+    Function {
+        name: &'static str,
+        arguments: Vec<ValueType>,
+        returns: ValueType,
+        code: ModuleFunction,
+    },
+}
+
+impl Declaration {
+    fn name(&self) -> &'static str {
+        match self {
+            Declaration::Integer(name)
+            | Declaration::Float(name)
+            | Declaration::String(name)
+            | Declaration::Structure(name, _)
+            | Declaration::Array(name, _)
+            | Declaration::Dictionary(name, _)
+            | Declaration::Function { name, .. } => name,
+        }
+    }
+}
+
+/// A yara module written in rust
+///
+/// libyara only knows about modules at build time, so the name of the
+/// module must be listed in the comma separated `YARA2_RUST_MODULES`
+/// environment variable when building this crate with the `static`
+/// feature. The module is then registered with [`register_module`] before
+/// compiling rules importing it.
+///
+/// # Example
+///
+/// ```no_run
+/// use yara2::*;
+///
+/// struct Reputation;
+///
+/// fn is_known(args: &[ModuleValue]) -> ModuleValue {
+///     let known = args[0].as_str() == Some("d41d8cd98f00b204e9800998ecf8427e");
+///     ModuleValue::Integer(known as i64)
+/// }
+///
+/// impl CustomModule for Reputation {
+///     fn name(&self) -> &str {
+///         "reputation"
+///     }
+///
+///     fn declarations(&self) -> Vec<Declaration> {
+///         vec![
+///             Declaration::Integer("size"),
+///             Declaration::Function {
+///                 name: "is_known",
+///                 arguments: vec![ValueType::String],
+///                 returns: ValueType::Integer,
+///                 code: is_known,
+///             },
+///         ]
+///     }
+///
+///     fn load(&self, data: &[u8], _module_data: Option<&[u8]>) -> ModuleValue {
+///         let mut fields = std::collections::BTreeMap::new();
+///         fields.insert("size".to_string(), ModuleValue::Integer(data.len() as i64));
+///         ModuleValue::Structure(fields)
+///     }
+/// }
+///
+/// register_module(Reputation).unwrap();
+/// let mut compiler = Compiler::new().unwrap();
+/// compiler
+///     .add_rule_str(r#"import "reputation" rule big { condition: reputation.size > 1024 }"#, None)
+///     .unwrap();
+/// ```
+pub trait CustomModule: Send + Sync + 'static {
+    /// Name used by rules to import the module
+    fn name(&self) -> &str;
+
+    /// Layout of the data built by the module, and its functions
+    fn declarations(&self) -> Vec<Declaration>;
+
+    /// Build the module data for a scan
+    ///
+    /// `data` is the scanned data, only its first region for process
+    /// scans. `module_data` is the data set with
+    /// [`Scanner::set_module_data`](crate::Scanner::set_module_data).
+    /// The returned value should be a structure following the declarations,
+    /// fields not declared or of another type are ignored.
+    fn load(&self, data: &[u8], module_data: Option<&[u8]>) -> ModuleValue;
+}
+
+/// Make a module written in rust available to the rules importing it
+///
+/// Registering a module again replaces it for the next compilations and
+/// scans. [`Error::UnknownModule`] is returned when the module was not
+/// built into libyara.
+pub fn register_module<M: CustomModule>(module: M) -> Result<()> {
+    let name = module.name().to_string();
+    if !BUILT_MODULES.split(',').any(|built| built.trim() == name) {
+        return Err(Error::UnknownModule(name));
+    }
+
+    let declarations = module
+        .declarations()
+        .iter()
+        .map(Field::new)
+        .collect::<Result<Vec<_>>>()?;
+    let registered = Registered {
+        module: Box::new(module),
+        declarations,
+    };
+    MODULES.write().unwrap().insert(name, Arc::new(registered));
+    Ok(())
+}
+
+struct Registered {
+    module: Box<dyn CustomModule>,
+    declarations: Vec<Field>,
+}
+
+/// A [`Declaration`] with the strings libyara needs
+///
+/// libyara keeps pointers to function formats instead of copying them,
+/// as they are literals in modules written in C. They are leaked here
+/// so they outlive any compiled rules.
+struct Field {
+    name: CString,
+    kind: FieldKind,
+}
+
+enum FieldKind {
+    Leaf(ValueType),
+    Structure(Vec<Field>),
+    Array(Box<Field>),
+    Dictionary(Box<Field>),
+    Function {
+        arguments: &'static CStr,
+        returns: &'static CStr,
+        code: ModuleFunction,
+    },
+}
+
+impl Field {
+    fn new(declaration: &Declaration) -> Result<Field> {
+        let name = declaration.name();
+        let kind = match declaration {
+            Declaration::Integer(_) => FieldKind::Leaf(ValueType::Integer),
+            Declaration::Float(_) => FieldKind::Leaf(ValueType::Float),
+            Declaration::String(_) => FieldKind::Leaf(ValueType::String),
+            Declaration::Structure(_, fields) => {
+                FieldKind::Structure(fields.iter().map(Field::new).collect::<Result<Vec<_>>>()?)
+            }
+            Declaration::Array(_, item) => FieldKind::Array(Box::new(Field::new(item)?)),
+            Declaration::Dictionary(_, item) => FieldKind::Dictionary(Box::new(Field::new(item)?)),
+            Declaration::Function {
+                arguments,
+                returns,
+                code,
+                ..
+            } => FieldKind::Function {
+                arguments: leak(arguments.iter().map(|t| t.format()).collect()),
+                returns: leak(returns.format().to_string()),
+                code: *code,
+            },
+        };
+        let name = CString::new(name).map_err(|_| Error::InvalidDeclaration(name.to_string()))?;
+        Ok(Field { name, kind })
+    }
+
+    /// Create the libyara object for this field under `parent`
+    ///
+    /// Items of arrays and dictionaries are named after their container.
+    unsafe fn declare(&self, name: &CStr, parent: *mut bindings::YR_OBJECT) -> c_int {
+        let mut object = ptr::null_mut();
+        let object_type = match &self.kind {
+            FieldKind::Leaf(ValueType::Integer) => bindings::OBJECT_TYPE_INTEGER,
+            FieldKind::Leaf(ValueType::Float) => bindings::OBJECT_TYPE_FLOAT,
+            FieldKind::Leaf(ValueType::String) => bindings::OBJECT_TYPE_STRING,
+            FieldKind::Structure(_) => bindings::OBJECT_TYPE_STRUCTURE,
+            FieldKind::Array(_) => bindings::OBJECT_TYPE_ARRAY,
+            FieldKind::Dictionary(_) => bindings::OBJECT_TYPE_DICTIONARY,
+            FieldKind::Function {
+                arguments,
+                returns,
+                code,
+            } => {
+                let result = bindings::yr_object_function_create(
+                    name.as_ptr(),
+                    arguments.as_ptr(),
+                    returns.as_ptr(),
+                    Some(call_function),
+                    parent,
+                    &mut object,
+                );
+                if result == bindings::ERROR_SUCCESS as c_int {
+                    (*object).data = *code as *mut c_void;
+                }
+                return result;
+            }
+        };
+
+        let result =
+            bindings::yr_object_create(object_type as i8, name.as_ptr(), parent, &mut object);
+        if result != bindings::ERROR_SUCCESS as c_int {
+            return result;
+        }
+        match &self.kind {
+            FieldKind::Structure(fields) => declare_all(fields, object),
+            FieldKind::Array(item) | FieldKind::Dictionary(item) => item.declare(name, object),
+            _ => result,
+        }
+    }
+
+    /// Set the values matching this field in the module object
+    ///
+    /// `path` locates the field from the root of the module, in the
+    /// syntax understood by `yr_object_set_*`.
+    unsafe fn load(
+        &self,
+        value: &ModuleValue,
+        object: *mut bindings::YR_OBJECT,
+        path: &str,
+    ) -> c_int {
+        match (&self.kind, value) {
+            (FieldKind::Leaf(value_type), _) => match CString::new(path) {
+                Ok(path) if !value.is_undefined() => {
+                    set_value(object, Some(&path), *value_type, value)
+                }
+                _ => bindings::ERROR_SUCCESS as c_int,
+            },
+            (FieldKind::Structure(fields), ModuleValue::Structure(_)) => {
+                load_all(fields, value, object, path)
+            }
+            (FieldKind::Array(item), ModuleValue::Array(items)) => {
+                for (index, value) in items.iter().enumerate() {
+                    let result = item.load(value, object, &format!("{}[{}]", path, index));
+                    if result != bindings::ERROR_SUCCESS as c_int {
+                        return result;
+                    }
+                }
+                bindings::ERROR_SUCCESS as c_int
+            }
+            (FieldKind::Dictionary(item), ModuleValue::Dictionary(entries)) => {
+                // Keys are quoted in paths and cannot contain quotes
+                for (key, value) in entries.iter().filter(|(key, _)| !key.contains('"')) {
+                    let result = item.load(value, object, &format!("{}[\"{}\"]", path, key));
+                    if result != bindings::ERROR_SUCCESS as c_int {
+                        return result;
+                    }
+                }
+                bindings::ERROR_SUCCESS as c_int
+            }
+            _ => bindings::ERROR_SUCCESS as c_int,
+        }
+    }
+}
+
+fn leak(format: String) -> &'static CStr {
+    Box::leak(CString::new(format).unwrap_or_default().into_boxed_c_str())
+}
+
+unsafe fn declare_all(fields: &[Field], parent: *mut bindings::YR_OBJECT) -> c_int {
+    for field in fields {
+        let result = field.declare(&field.name, parent);
+        if result != bindings::ERROR_SUCCESS as c_int {
+            return result;
+        }
+    }
+    bindings::ERROR_SUCCESS as c_int
+}
+
+unsafe fn load_all(
+    fields: &[Field],
+    value: &ModuleValue,
+    object: *mut bindings::YR_OBJECT,
+    path: &str,
+) -> c_int {
+    for field in fields {
+        let name = field.name.to_string_lossy();
+        if let Some(value) = value.get(&name) {
+            let path = match path {
+                "" => name.to_string(),
+                _ => format!("{}.{}", path, name),
+            };
+            let result = field.load(value, object, &path);
+            if result != bindings::ERROR_SUCCESS as c_int {
+                return result;
+            }
+        }
+    }
+    bindings::ERROR_SUCCESS as c_int
+}
+
+/// Set an integer, float or string object, or the field at `path` below it
+///
+/// A value of another type is set as undefined.
+unsafe fn set_value(
+    object: *mut bindings::YR_OBJECT,
+    path: Option<&CStr>,
+    value_type: ValueType,
+    value: &ModuleValue,
+) -> c_int {
+    let (field, arg): (*const c_char, *const c_char) = match path {
+        Some(path) => (b"%s\0".as_ptr() as *const c_char, path.as_ptr()),
+        None => (ptr::null(), ptr::null()),
+    };
+    match (value_type, value) {
+        (ValueType::Integer, ModuleValue::Integer(i)) => {
+            bindings::yr_object_set_integer(*i, object, field, arg)
+        }
+        (ValueType::Integer, _) => {
+            bindings::yr_object_set_integer(bindings::UNDEFINED, object, field, arg)
+        }
+        (ValueType::Float, ModuleValue::Float(f)) => {
+            bindings::yr_object_set_float(*f, object, field, arg)
+        }
+        (ValueType::Float, _) => bindings::yr_object_set_float(f64::NAN, object, field, arg),
+        (ValueType::String, ModuleValue::String(s)) => bindings::yr_object_set_string(
+            s.as_ptr() as *const c_char,
+            s.len() as _,
+            object,
+            field,
+            arg,
+        ),
+        (ValueType::String, _) => {
+            bindings::yr_object_set_string(ptr::null(), 0, object, field, arg)
+        }
+    }
+}
+
+fn registered(name: *const c_char) -> Option<Arc<Registered>> {
+    let name = unsafe { CStr::from_ptr(name) }.to_string_lossy();
+    MODULES.read().ok()?.get(name.as_ref()).cloned()
+}
+
+/// Declarations of a module written in rust, called by the glue
+/// generated in `build.rs`
+#[no_mangle]
+unsafe extern "C" fn yara2_rust_module_declarations(
+    name: *const c_char,
+    module: *mut bindings::YR_OBJECT,
+) -> c_int {
+    match registered(name) {
+        Some(registered) => declare_all(&registered.declarations, module),
+        None => bindings::ERROR_UNKNOWN_MODULE as c_int,
+    }
+}
+
+/// Load of a module written in rust, called by the glue generated in
+/// `build.rs`
+#[no_mangle]
+unsafe extern "C" fn yara2_rust_module_load(
+    name: *const c_char,
+    module_object: *mut bindings::YR_OBJECT,
+    data: *const u8,
+    data_size: bindings::size_t,
+    module_data: *const c_void,
+    module_data_size: bindings::size_t,
+) -> c_int {
+    let registered = match registered(name) {
+        Some(registered) => registered,
+        None => return bindings::ERROR_UNKNOWN_MODULE as c_int,
+    };
+    let data = if data.is_null() {
+        &[][..]
+    } else {
+        std::slice::from_raw_parts(data, data_size as usize)
+    };
+    let module_data = if module_data.is_null() {
+        None
+    } else {
+        Some(std::slice::from_raw_parts(
+            module_data as *const u8,
+            module_data_size as usize,
+        ))
+    };
+
+    let value = catch_unwind(AssertUnwindSafe(|| {
+        registered.module.load(data, module_data)
+    }));
+    match value {
+        Ok(value) => load_all(&registered.declarations, &value, module_object, ""),
+        Err(_) => bindings::ERROR_INTERNAL_FATAL_ERROR as c_int,
+    }
+}
+
+/// Entry point of every function declared by a module written in rust
+///
+/// The rust function is kept in the `data` of the function object.
+unsafe extern "C" fn call_function(
+    args: *mut bindings::YR_VALUE,
+    _context: *mut bindings::YR_SCAN_CONTEXT,
+    function: *mut bindings::YR_OBJECT_FUNCTION,
+) -> c_int {
+    let function = &*function;
+    let code: ModuleFunction = std::mem::transmute(function.data);
+    let format = CStr::from_ptr(function.prototypes[0].arguments_fmt).to_bytes();
+    let arguments = format
+        .iter()
+        .enumerate()
+        .map(|(index, value_type)| {
+            let arg = *args.add(index);
+            match value_type {
+                b'i' => ModuleValue::Integer(arg.i),
+                b'f' => ModuleValue::Float(arg.d),
+                b's' if !arg.ss.is_null() => {
+                    let bytes = std::slice::from_raw_parts(
+                        (*arg.ss).c_string.as_ptr() as *const u8,
+                        (*arg.ss).length as usize,
+                    );
+                    ModuleValue::String(String::from_utf8_lossy(bytes).to_string())
+                }
+                _ => ModuleValue::Undefined,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let value = catch_unwind(|| code(&arguments)).unwrap_or(ModuleValue::Undefined);
+    let return_type = match (*function.return_obj).type_ as u32 {
+        bindings::OBJECT_TYPE_FLOAT => ValueType::Float,
+        bindings::OBJECT_TYPE_STRING => ValueType::String,
+        _ => ValueType::Integer,
+    };
+    set_value(function.return_obj, None, return_type, &value)
+}
@@ -0,0 +1,44 @@
+mod custom;
+mod value;
+
+pub use self::custom::*;
+pub use self::value::*;
+
+use crate::bindings;
+use serde::Serialize;
+
+/// Data built by a module imported by the rules, such as `pe` or `elf`
+///
+/// # Example
+///
+/// ```
+/// use yara2::*;
+/// let mut compiler = Compiler::new().unwrap();
+/// compiler
+///     .add_rule_str(r#"import "tests" rule always { condition: true }"#, None)
+///     .unwrap();
+/// let rules = compiler.compile().unwrap();
+///
+/// let results = rules
+///     .scan_memory_with_modules(b"data", ScanOptions::default())
+///     .unwrap();
+/// let tests = results.module("tests").unwrap();
+/// let one = tests.get("constants").and_then(|c| c.get("one"));
+/// assert_eq!(one.and_then(ModuleValue::as_integer), Some(1));
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Module {
+    pub name: String,
+    pub value: ModuleValue,
+}
+
+impl Module {
+    /// Convert the root object of a module, as reported by
+    /// `CALLBACK_MSG_MODULE_IMPORTED`
+    pub(crate) fn from_object(object: *const bindings::YR_OBJECT) -> Module {
+        Module {
+            name: unsafe { identifier((*object).identifier) },
+            value: ModuleValue::from_object(object).unwrap_or(ModuleValue::Undefined),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use crate::bindings;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
+/// Value of a field in the data built by a module
+///
+/// Modules expose their data as a tree of structures, arrays and
+/// dictionaries, with integers, floats and strings as leaves. Fields the
+/// module left unset are [`ModuleValue::Undefined`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ModuleValue {
+    Undefined,
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Structure(BTreeMap<String, ModuleValue>),
+    Array(Vec<ModuleValue>),
+    Dictionary(BTreeMap<String, ModuleValue>),
+}
+
+impl ModuleValue {
+    /// Field of a structure or entry of a dictionary
+    pub fn get(&self, key: &str) -> Option<&ModuleValue> {
+        match self {
+            ModuleValue::Structure(fields) | ModuleValue::Dictionary(fields) => fields.get(key),
+            _ => None,
+        }
+    }
+
+    /// Item of an array
+    pub fn index(&self, index: usize) -> Option<&ModuleValue> {
+        match self {
+            ModuleValue::Array(items) => items.get(index),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            ModuleValue::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            ModuleValue::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ModuleValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Whether the module left this value unset
+    pub fn is_undefined(&self) -> bool {
+        *self == ModuleValue::Undefined
+    }
+
+    /// Convert an object tree built by libyara
+    ///
+    /// Functions and regular expressions carry no data and are converted
+    /// to `None`, they are left out of their parent structure or
+    /// dictionary, and are undefined items of their parent array.
+    pub(crate) fn from_object(object: *const bindings::YR_OBJECT) -> Option<ModuleValue> {
+        if object.is_null() {
+            return Some(ModuleValue::Undefined);
+        }
+        let object = unsafe { &*object };
+        let value = match object.type_ as u32 {
+            bindings::OBJECT_TYPE_INTEGER => match unsafe { object.value.i } {
+                bindings::UNDEFINED => ModuleValue::Undefined,
+                i => ModuleValue::Integer(i),
+            },
+            bindings::OBJECT_TYPE_FLOAT => {
+                let d = unsafe { object.value.d };
+                if d.is_nan() || unsafe { object.value.i } == bindings::UNDEFINED {
+                    ModuleValue::Undefined
+                } else {
+                    ModuleValue::Float(d)
+                }
+            }
+            bindings::OBJECT_TYPE_STRING => {
+                let ss = unsafe { object.value.ss };
+                if ss.is_null() {
+                    ModuleValue::Undefined
+                } else {
+                    let bytes = unsafe {
+                        std::slice::from_raw_parts(
+                            (*ss).c_string.as_ptr() as *const u8,
+                            (*ss).length as usize,
+                        )
+                    };
+                    ModuleValue::String(String::from_utf8_lossy(bytes).to_string())
+                }
+            }
+            bindings::OBJECT_TYPE_STRUCTURE => {
+                let structure =
+                    unsafe { &*(object as *const _ as *const bindings::YR_OBJECT_STRUCTURE) };
+                let mut fields = BTreeMap::new();
+                let mut member = structure.members;
+                while !member.is_null() {
+                    let current = unsafe { &*member };
+                    let value = if current.object.is_null() {
+                        None
+                    } else {
+                        Self::from_object(current.object)
+                    };
+                    if let Some(value) = value {
+                        let name = unsafe { identifier((*current.object).identifier) };
+                        fields.insert(name, value);
+                    }
+                    member = current.next;
+                }
+                ModuleValue::Structure(fields)
+            }
+            bindings::OBJECT_TYPE_ARRAY => {
+                let array = unsafe { &*(object as *const _ as *const bindings::YR_OBJECT_ARRAY) };
+                let mut items = Vec::new();
+                if !array.items.is_null() {
+                    let count = unsafe { (*array.items).count } as usize;
+                    let objects = unsafe { (*array.items).objects.as_ptr() };
+                    for i in 0..count {
+                        let item = unsafe { *objects.add(i) };
+                        // keep the index of the following items
+                        items.push(Self::from_object(item).unwrap_or(ModuleValue::Undefined));
+                    }
+                }
+                ModuleValue::Array(items)
+            }
+            bindings::OBJECT_TYPE_DICTIONARY => {
+                let dictionary =
+                    unsafe { &*(object as *const _ as *const bindings::YR_OBJECT_DICTIONARY) };
+                let mut entries = BTreeMap::new();
+                if !dictionary.items.is_null() {
+                    let used = unsafe { (*dictionary.items).used } as usize;
+                    let objects = unsafe { (*dictionary.items).objects.as_ptr() };
+                    for i in 0..used {
+                        let entry = unsafe { &*objects.add(i) };
+                        if let Some(value) = Self::from_object(entry.obj) {
+                            entries.insert(unsafe { identifier(entry.key) }, value);
+                        }
+                    }
+                }
+                ModuleValue::Dictionary(entries)
+            }
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+/// Copy a nul terminated name owned by libyara
+pub(crate) unsafe fn identifier(pointer: *const c_char) -> String {
+    if pointer.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(pointer).to_string_lossy().to_string()
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{Match, Rule};
+use serde::Serialize;
+#[cfg(target_os = "linux")]
+use std::os::raw::c_int;
+
+/// A mapping of the memory of a process, as listed in `/proc/<pid>/maps`
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct MemoryRegion {
+    /// First address of the region.
+    pub start: u64,
+    /// Address following the last byte of the region.
+    pub end: u64,
+    /// Permissions such as `r-xp`, `p` for private or `s` for shared mappings.
+    pub permissions: String,
+    /// Offset of the region in the mapped file.
+    pub offset: u64,
+    /// Mapped file, or a pseudo path such as `[heap]` or `[stack]`.
+    /// `None` for anonymous mappings.
+    pub path: Option<String>,
+}
+
+impl MemoryRegion {
+    /// Whether `address` falls within the region
+    pub fn contains(&self, address: u64) -> bool {
+        self.start <= address && address < self.end
+    }
+
+    /// Parse a line of `/proc/<pid>/maps`
+    ///
+    /// `start-end perms offset dev inode path`, the path may contain
+    /// spaces and is missing for anonymous mappings.
+    fn parse(line: &str) -> Option<MemoryRegion> {
+        let mut fields = line.splitn(6, ' ');
+        let mut range = fields.next()?.splitn(2, '-');
+        let start = u64::from_str_radix(range.next()?, 16).ok()?;
+        let end = u64::from_str_radix(range.next()?, 16).ok()?;
+        let permissions = fields.next()?.to_string();
+        let offset = u64::from_str_radix(fields.next()?, 16).ok()?;
+        let path = fields
+            .nth(2)
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(str::to_string);
+
+        Some(MemoryRegion {
+            start,
+            end,
+            permissions,
+            offset,
+            path,
+        })
+    }
+}
+
+/// Memory regions of a running process, sorted by address
+///
+/// # Arguments
+/// `pid` - process id
+#[cfg(target_os = "linux")]
+pub fn memory_regions(pid: c_int) -> crate::Result<Vec<MemoryRegion>> {
+    let maps = std::fs::read_to_string(format!("/proc/{}/maps", pid))?;
+    Ok(maps.lines().filter_map(MemoryRegion::parse).collect())
+}
+
+/// Rules matching a process, with the memory regions of the process
+#[derive(Clone, Debug, Serialize)]
+pub struct ProcessScanResults {
+    pub rules: Vec<Rule>,
+    /// Regions listed just before the scan, sorted by address.
+    pub regions: Vec<MemoryRegion>,
+}
+
+impl ProcessScanResults {
+    /// Region containing a match, telling whether it was found in the
+    /// heap, the stack or a mapped module
+    pub fn region_for(&self, m: &Match) -> Option<&MemoryRegion> {
+        let address = m.offset as u64;
+        let index = match self
+            .regions
+            .binary_search_by(|region| region.start.cmp(&address))
+        {
+            Ok(index) => index,
+            Err(0) => return None,
+            Err(index) => index - 1,
+        };
+        self.regions
+            .get(index)
+            .filter(|region| region.contains(address))
+    }
+}
+
+/// A running process, as listed in `/proc`
+#[cfg(target_os = "linux")]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ProcessInfo {
+    pub pid: c_int,
+    /// Name of the executable, possibly truncated by the kernel.
+    pub name: String,
+    /// Arguments the process was started with.
+    pub cmdline: Vec<String>,
+}
+
+#[cfg(target_os = "linux")]
+impl ProcessInfo {
+    /// Read the details of a process, `None` for kernel threads, which have
+    /// no command line, or processes that exited
+    fn read(pid: c_int) -> Option<ProcessInfo> {
+        let cmdline = std::fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+        if cmdline.is_empty() {
+            return None;
+        }
+        let name = std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+
+        Some(ProcessInfo {
+            pid,
+            name: name.trim_end().to_string(),
+            cmdline: cmdline
+                .split(|b| *b == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| String::from_utf8_lossy(arg).to_string())
+                .collect(),
+        })
+    }
+}
+
+/// Outcome of scanning one of many processes
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+pub struct ProcessScan {
+    pub process: ProcessInfo,
+    /// Matching rules, or why the process could not be scanned, usually
+    /// [`Error::CouldNotAttachToProcess`](crate::Error::CouldNotAttachToProcess)
+    /// when lacking permissions.
+    pub result: crate::Result<Vec<Rule>>,
+}
+
+/// Running processes, except kernel threads and the current process
+#[cfg(target_os = "linux")]
+pub fn processes() -> crate::Result<Vec<ProcessInfo>> {
+    let own_pid = std::process::id() as c_int;
+    let mut processes = std::fs::read_dir("/proc")?
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<c_int>().ok())
+        .filter(|pid| *pid != own_pid)
+        .filter_map(ProcessInfo::read)
+        .collect::<Vec<_>>();
+    processes.sort_by_key(|process| process.pid);
+    Ok(processes)
+}
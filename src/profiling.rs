@@ -0,0 +1,111 @@
+use crate::rules::MAX_SCAN_THREADS;
+use crate::{Rule, Rules};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::time::Duration;
+
+/// Cost of a rule over the scans done so far
+#[derive(Clone, Debug, Serialize)]
+pub struct RuleProfile {
+    pub namespace: String,
+    pub identifier: String,
+    /// Time spent verifying the strings of the rule.
+    pub time_cost: Duration,
+    /// Number of atoms extracted from the strings of the rule. Short or
+    /// common atoms trigger many string verifications.
+    pub num_atoms: usize,
+}
+
+/// Profiling information gathered by libyara over the scans done so far
+#[derive(Clone, Debug, Serialize)]
+pub struct Profile {
+    /// Time spent scanning with the rules.
+    pub total: Duration,
+    /// Cost of each rule, slowest first.
+    pub rules: Vec<RuleProfile>,
+}
+
+impl Profile {
+    /// The `count` slowest rules
+    pub fn slowest(&self, count: usize) -> &[RuleProfile] {
+        &self.rules[..count.min(self.rules.len())]
+    }
+
+    /// The `count` rules with the most atoms
+    ///
+    /// libyara 3.11 only counts atoms per rule, not per string, so this
+    /// points at the rules whose strings are likely the most costly rather
+    /// than at the strings themselves.
+    pub fn most_atoms(&self, count: usize) -> Vec<&RuleProfile> {
+        let mut rules = self.rules.iter().collect::<Vec<_>>();
+        rules.sort_by_key(|rule| Reverse(rule.num_atoms));
+        rules.truncate(count);
+        rules
+    }
+}
+
+impl Rules {
+    /// Time spent on each rule by the scans done so far
+    ///
+    /// Times are only measured when libyara is built with profiling, which
+    /// the `profiling` feature does when linking statically. The costs keep
+    /// adding up until [`Rules::reset_profile`] is called.
+    ///
+    /// libyara updates the costs from the scanning threads without
+    /// synchronization, taking the rules mutably ensures no scan is
+    /// running while they are read.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use yara2::*;
+    /// let mut compiler = Compiler::new().unwrap();
+    /// compiler.add_rule_file("rules.yar", None).unwrap();
+    /// let mut rules = compiler.compile().unwrap();
+    ///
+    /// for path in std::env::args().skip(1) {
+    ///     let _ = rules.scan_file(path, ScanOptions::default());
+    /// }
+    /// for rule in rules.profile().slowest(10) {
+    ///     println!("{}:{} {:?}", rule.namespace, rule.identifier, rule.time_cost);
+    /// }
+    /// ```
+    pub fn profile(&mut self) -> Profile {
+        let mut rules = self
+            .raw_rules()
+            .map(|rule| unsafe {
+                let time_cost = (*rule).time_cost;
+                let Rule {
+                    namespace,
+                    identifier,
+                    ..
+                } = Rule::describe(&*rule);
+                RuleProfile {
+                    namespace,
+                    identifier,
+                    time_cost: Duration::from_micros(time_cost.max(0) as u64),
+                    num_atoms: (*rule).num_atoms.max(0) as usize,
+                }
+            })
+            .collect::<Vec<_>>();
+        rules.sort_by_key(|rule| Reverse(rule.time_cost));
+
+        Profile {
+            total: Duration::from_micros(unsafe { (*self.inner).time_cost }),
+            rules,
+        }
+    }
+
+    /// Start measuring again from zero
+    pub fn reset_profile(&mut self) {
+        for rule in self.raw_rules() {
+            unsafe {
+                (*rule).time_cost = 0;
+                (*rule).time_cost_per_thread = [0; MAX_SCAN_THREADS];
+            }
+        }
+        unsafe {
+            (*self.inner).time_cost = 0;
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::bindings;
+use serde::{Serialize, Serializer};
+
+bitflags! {
+    /// Modifiers of a string, as compiled by libyara
+    ///
+    /// The vendored libyara 3.11 has no `private`, `base64` nor `base64wide`
+    /// modifiers, rules using them fail to compile, so there are no flags
+    /// for them.
+    ///
+    /// Serialized as the list of the names of the flags set, such as
+    /// `["NO_CASE", "WIDE"]`.
+    pub struct StringFlags: u32 {
+        /// Hex string, such as `{ 4D 5A }`.
+        const HEXADECIMAL = bindings::STRING_GFLAGS_HEXADECIMAL;
+        /// `nocase` text or `/i` regular expression.
+        const NO_CASE = bindings::STRING_GFLAGS_NO_CASE;
+        /// `ascii`, also set for text strings without `wide`.
+        const ASCII = bindings::STRING_GFLAGS_ASCII;
+        const WIDE = bindings::STRING_GFLAGS_WIDE;
+        /// Regular expression, such as `/md5: [0-9a-f]{32}/`.
+        const REGEXP = bindings::STRING_GFLAGS_REGEXP;
+        const FULL_WORD = bindings::STRING_GFLAGS_FULL_WORD;
+        /// String declared as `$`, without a name.
+        const ANONYMOUS = bindings::STRING_GFLAGS_ANONYMOUS;
+        /// Piece of a hex string split on a large jump, other than the last.
+        const CHAIN_PART = bindings::STRING_GFLAGS_CHAIN_PART;
+        /// Last piece of a hex string split on a large jump.
+        const CHAIN_TAIL = bindings::STRING_GFLAGS_CHAIN_TAIL;
+        /// `/s` regular expression, where `.` matches new lines.
+        const DOT_ALL = bindings::STRING_GFLAGS_DOT_ALL;
+        const XOR = bindings::STRING_GFLAGS_XOR;
+    }
+}
+
+/// Names of the flags, in the order they are serialized
+const FLAG_NAMES: [(&str, StringFlags); 11] = [
+    ("HEXADECIMAL", StringFlags::HEXADECIMAL),
+    ("NO_CASE", StringFlags::NO_CASE),
+    ("ASCII", StringFlags::ASCII),
+    ("WIDE", StringFlags::WIDE),
+    ("REGEXP", StringFlags::REGEXP),
+    ("FULL_WORD", StringFlags::FULL_WORD),
+    ("ANONYMOUS", StringFlags::ANONYMOUS),
+    ("CHAIN_PART", StringFlags::CHAIN_PART),
+    ("CHAIN_TAIL", StringFlags::CHAIN_TAIL),
+    ("DOT_ALL", StringFlags::DOT_ALL),
+    ("XOR", StringFlags::XOR),
+];
+
+impl Serialize for StringFlags {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            FLAG_NAMES
+                .iter()
+                .filter(|(_, flag)| self.contains(*flag))
+                .map(|(name, _)| name),
+        )
+    }
+}
+
+impl StringFlags {
+    /// Flags of a string, dropping the ones libyara only uses internally
+    pub(crate) fn from_g_flags(g_flags: i32) -> StringFlags {
+        StringFlags::from_bits_truncate(g_flags as u32)
+    }
+
+    /// Whether the string is a piece of a hex string split on a large jump
+    pub fn is_chained(&self) -> bool {
+        self.intersects(StringFlags::CHAIN_PART | StringFlags::CHAIN_TAIL)
+    }
+}
+
+/// How a string was written in the rule
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub enum StringKind {
+    /// Text between double quotes.
+    Text,
+    /// Hex string between braces.
+    Hex,
+    /// Regular expression between slashes.
+    Regex,
+}
+
+impl From<StringFlags> for StringKind {
+    fn from(flags: StringFlags) -> Self {
+        // hex strings with jumps or alternatives are compiled as regular
+        // expressions, the hex flag tells them apart
+        if flags.contains(StringFlags::HEXADECIMAL) {
+            StringKind::Hex
+        } else if flags.contains(StringFlags::REGEXP) {
+            StringKind::Regex
+        } else {
+            StringKind::Text
+        }
+    }
+}
@@ -1,16 +1,26 @@
+mod flags;
 mod metadata;
 mod tag;
 mod yr_string;
 
+pub use self::flags::{StringFlags, StringKind};
 use self::metadata::*;
+pub use self::metadata::{Metadata, MetadataValue};
 use self::tag::*;
 use self::yr_string::*;
+pub use self::yr_string::{Match, YrString};
 use crate::bindings;
 use serde::Serialize;
 use std::ffi::CStr;
 
 #[derive(Clone, Debug, Serialize)]
 pub struct Rule {
+    /// Whether the rule matched the scanned data. Only rules that matched
+    /// are reported unless
+    /// [`ScanOptions::report_non_matching`](crate::ScanOptions::report_non_matching)
+    /// is set. Always `false` for rules listed by
+    /// [`Rules::iter`](crate::Rules::iter).
+    pub matched: bool,
     pub identifier: String,
     pub namespace: String,
     pub metadata: Vec<Metadata>,
@@ -18,8 +28,16 @@ pub struct Rule {
     pub strings: Vec<YrString>,
 }
 
-impl From<&bindings::YR_RULE> for Rule {
-    fn from(rule: &bindings::YR_RULE) -> Self {
+impl Rule {
+    /// Describe a rule outside of a scan, without matches
+    pub(crate) fn describe(rule: &bindings::YR_RULE) -> Rule {
+        let strings = YrStringIterator::from(rule)
+            .map(YrString::describe)
+            .collect();
+        Rule::new(rule, false, strings)
+    }
+
+    fn new(rule: &bindings::YR_RULE, matched: bool, strings: Vec<YrString>) -> Rule {
         let id = unsafe {
             CStr::from_ptr(rule.get_identifier())
                 .to_str()
@@ -31,15 +49,24 @@ impl From<&bindings::YR_RULE> for Rule {
             .unwrap()
             .to_owned();
         Rule {
+            matched,
             identifier: id,
             namespace: ns,
             tags: TagIterator::from(rule).collect(),
             metadata: MetadataIterator::from(rule).collect(),
-            strings: YrStringIterator::from(rule).map(YrString::from).collect(),
+            strings,
         }
     }
 }
 
+impl From<&bindings::YR_RULE> for Rule {
+    fn from(rule: &bindings::YR_RULE) -> Self {
+        let matched = rule.t_flags[get_tidx() as usize] as u32 & bindings::RULE_TFLAGS_MATCH != 0;
+        let strings = YrStringIterator::from(rule).map(YrString::from).collect();
+        Rule::new(rule, matched, strings)
+    }
+}
+
 /// Get the Yara thread id.
 fn get_tidx() -> i32 {
     unsafe { bindings::yr_get_tidx() }
@@ -1,12 +1,17 @@
 use crate::bindings;
 use crate::rule::get_tidx;
+use crate::rule::{StringFlags, StringKind};
 use serde::Serialize;
 use std::ffi::CStr;
 use std::marker;
 
 #[derive(Clone, Debug, Serialize)]
 pub struct Match {
-    /// Offset of the match within the scanning area.
+    /// Address of the block of data containing the match, zero unless
+    /// scanning a process or memory blocks.
+    pub base: usize,
+    /// Offset of the match, from the start of the scanned data or the
+    /// virtual address of the match in a process or memory blocks.
     pub offset: usize,
     /// Length of the file. Can be useful if the matcher string has not a fixed length.
     pub length: usize,
@@ -18,7 +23,11 @@ pub struct Match {
 pub struct YrString {
     /// Name of the string, with the '$'.
     pub identifier: String,
-    /// Matches of the string for the scan.
+    /// Modifiers of the string, such as `nocase`, `wide` or `xor`.
+    pub flags: StringFlags,
+    /// Whether the string is text, hex or a regular expression.
+    pub kind: StringKind,
+    /// Matches of the string for the scan, empty outside of a scan.
     pub matches: Vec<Match>,
 }
 
@@ -55,20 +64,35 @@ impl<'a> Iterator for YrStringIterator<'a> {
     }
 }
 
-impl From<&bindings::YR_STRING> for YrString {
-    fn from(string: &bindings::YR_STRING) -> Self {
+impl YrString {
+    /// Describe a string outside of a scan, without matches
+    pub(crate) fn describe(string: &bindings::YR_STRING) -> Self {
         let identifier = unsafe { CStr::from_ptr(string.get_identifier()) }
             .to_str()
             .unwrap()
             .to_owned();
+
+        let flags = StringFlags::from_g_flags(string.g_flags);
+
+        YrString {
+            identifier,
+            flags,
+            kind: StringKind::from(flags),
+            matches: Vec::new(),
+        }
+    }
+}
+
+impl From<&bindings::YR_STRING> for YrString {
+    fn from(string: &bindings::YR_STRING) -> Self {
         let tidx = get_tidx();
         let matches = MatchIterator::from(&string.matches[tidx as usize])
             .map(Match::from)
             .collect();
 
         YrString {
-            identifier,
             matches,
+            ..YrString::describe(string)
         }
     }
 }
@@ -104,7 +128,8 @@ impl<'a> Iterator for MatchIterator<'a> {
 impl<'a> From<&'a bindings::YR_MATCH> for Match {
     fn from(m: &bindings::YR_MATCH) -> Self {
         Match {
-            offset: m.offset as usize,
+            base: m.base as usize,
+            offset: (m.base + m.offset) as usize,
             length: m.match_length as usize,
             data: Vec::from(unsafe { std::slice::from_raw_parts(m.data, m.data_length as usize) }),
         }
@@ -0,0 +1,567 @@
+use crate::bindings;
+use crate::yara::{finalize, initialize};
+use crate::{Diagnostic, Error, Result, Rule, ScanOptions, ScanResults, Scanner};
+use std::convert::AsRef;
+use std::ffi::{CStr, CString};
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+use std::os::raw::c_void;
+use std::path::Path;
+use std::ptr;
+
+/// Maximum number of scans libyara can run concurrently on the same rules
+pub(crate) const MAX_SCAN_THREADS: usize = 32;
+
+/// A set of compiled yara rules
+///
+/// Rules are produced by [`Compiler::compile`](crate::Compiler::compile)
+/// and cannot be modified afterwards. They can be saved to disk and loaded
+/// back later, which avoids paying the compilation cost every time a
+/// process starts.
+///
+/// # Example
+///
+/// ```no_run
+/// use yara2::*;
+/// let mut compiler = Compiler::new().unwrap();
+/// compiler.add_rule_str("rule always { condition: true }", None).unwrap();
+/// compiler.compile().unwrap().save("rules.bin").unwrap();
+///
+/// let rules = Rules::load("rules.bin").unwrap();
+/// let results = rules.scan_memory(b"some data", ScanOptions::default()).unwrap();
+/// ```
+///
+/// # Threads
+///
+/// Rules are `Send` and `Sync`, so they can be wrapped in an `Arc` and
+/// scanned from many threads at once. libyara keeps per thread match
+/// state in fixed slots, so at most 32 scans may run concurrently on the
+/// same rules. Scans beyond that limit fail with
+/// [`Error::TooManyScanThreads`] instead of waiting for a slot.
+///
+/// ```
+/// use std::sync::Arc;
+/// use yara2::*;
+/// let mut compiler = Compiler::new().unwrap();
+/// compiler.add_rule_str("rule always { condition: true }", None).unwrap();
+/// let rules = Arc::new(compiler.compile().unwrap());
+///
+/// let handles = (0..4)
+///     .map(|_| {
+///         let rules = rules.clone();
+///         std::thread::spawn(move || {
+///             rules
+///                 .scan_memory(b"some data", ScanOptions::default())
+///                 .unwrap()
+///                 .len()
+///         })
+///     })
+///     .collect::<Vec<_>>();
+/// for handle in handles {
+///     assert_eq!(handle.join().unwrap(), 1);
+/// }
+/// ```
+pub struct Rules {
+    pub(crate) inner: *mut bindings::YR_RULES,
+    warnings: Vec<Diagnostic>,
+}
+
+// libyara protects the shared scanning state of compiled rules with a
+// mutex and hands each scanning thread its own `tidx` slot, matches are
+// only ever read from the slot of the current thread. Nothing exposed
+// here mutates the rules outside of a scan.
+unsafe impl Send for Rules {}
+unsafe impl Sync for Rules {}
+
+impl Drop for Rules {
+    fn drop(&mut self) {
+        unsafe {
+            bindings::yr_rules_destroy(self.inner);
+        }
+        finalize().unwrap();
+    }
+}
+
+impl<'a> IntoIterator for &'a Rules {
+    type Item = Rule;
+    type IntoIter = RuleIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Rules of a compiled set, see [`Rules::iter`]
+pub struct RuleIterator<'a> {
+    raw: RawRules,
+    _rules: PhantomData<&'a Rules>,
+}
+
+impl<'a> Iterator for RuleIterator<'a> {
+    type Item = Rule;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.raw
+            .next()
+            .map(|rule| Rule::describe(unsafe { &*rule }))
+    }
+}
+
+/// Pointers to the rules of a compiled set, up to the null rule ending
+/// the list
+pub(crate) struct RawRules {
+    head: *mut bindings::YR_RULE,
+}
+
+impl Iterator for RawRules {
+    type Item = *mut bindings::YR_RULE;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.head.is_null() {
+            return None;
+        }
+
+        let rule = self.head;
+        if unsafe { (*rule).g_flags } as u32 & bindings::RULE_GFLAGS_NULL != 0 {
+            None
+        } else {
+            self.head = unsafe { self.head.offset(1) };
+            Some(rule)
+        }
+    }
+}
+
+impl Rules {
+    /// Takes ownership of rules produced by libyara
+    ///
+    /// The library must already be initialized when `inner` was created,
+    /// this adds a reference so it stays initialized as long as the rules live.
+    pub(crate) fn new(inner: *mut bindings::YR_RULES) -> Result<Rules> {
+        initialize().map(|_| Rules {
+            inner,
+            warnings: Vec::new(),
+        })
+    }
+
+    /// Keep the warnings reported while compiling the rules
+    pub(crate) fn with_warnings(mut self, warnings: Vec<Diagnostic>) -> Rules {
+        self.warnings = warnings;
+        self
+    }
+
+    /// Warnings reported by the compiler for these rules
+    ///
+    /// Empty for rules loaded by [`Rules::load`], warnings are not saved
+    /// with the compiled rules.
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+
+    /// Load rules previously written by [`Rules::save`]
+    ///
+    /// # Arguments
+    /// `path` - path to the compiled rules file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Rules> {
+        let name = path.as_ref().to_string_lossy().to_string();
+        let c_path = CString::new(name.as_str()).map_err(|_| Error::InvalidFile(name.clone()))?;
+
+        Self::load_with(|pointer| unsafe { bindings::yr_rules_load(c_path.as_ptr(), pointer) })
+            .map_err(|err| match err {
+                Error::UnknownYaraError(code)
+                    if code as u32 == bindings::ERROR_COULD_NOT_OPEN_FILE =>
+                {
+                    Error::InvalidFile(name)
+                }
+                err => err,
+            })
+    }
+
+    /// Load rules previously written by [`Rules::save_stream`]
+    ///
+    /// # Arguments
+    /// `reader` - source of the compiled rules
+    pub fn load_stream<R: Read>(reader: R) -> Result<Rules> {
+        let mut context = StreamContext::new(reader);
+        let mut stream = bindings::YR_STREAM {
+            user_data: &mut context as *mut StreamContext<R> as *mut c_void,
+            read: Some(stream_read::<R>),
+            write: None,
+        };
+
+        let result = Self::load_with(|pointer| unsafe {
+            bindings::yr_rules_load_stream(&mut stream, pointer)
+        });
+        context.into_result(result).map(|(rules, _)| rules)
+    }
+
+    /// Write the compiled rules to a file
+    ///
+    /// # Arguments
+    /// `path` - destination of the compiled rules
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let name = path.as_ref().to_string_lossy().to_string();
+        let c_path = CString::new(name.as_str()).map_err(|_| Error::InvalidFile(name.clone()))?;
+
+        match unsafe { bindings::yr_rules_save(self.inner, c_path.as_ptr()) } as u32 {
+            bindings::ERROR_COULD_NOT_OPEN_FILE => Err(Error::InvalidFile(name)),
+            code => Error::from_code(code as i32),
+        }
+    }
+
+    /// Write the compiled rules to any writer
+    ///
+    /// # Arguments
+    /// `writer` - destination of the compiled rules
+    pub fn save_stream<W: Write>(&self, writer: W) -> Result<()> {
+        let mut context = StreamContext::new(writer);
+        let mut stream = bindings::YR_STREAM {
+            user_data: &mut context as *mut StreamContext<W> as *mut c_void,
+            read: None,
+            write: Some(stream_write::<W>),
+        };
+
+        let result =
+            Error::from_code(unsafe { bindings::yr_rules_save_stream(self.inner, &mut stream) });
+        context
+            .into_result(result)
+            .and_then(|(_, mut writer)| writer.flush().map_err(Error::from))
+    }
+
+    /// List the rules of the set, with their metadata, tags and strings
+    ///
+    /// The rules are described as they were compiled, without matches.
+    /// Private rules are listed too.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yara2::*;
+    /// let mut compiler = Compiler::new().unwrap();
+    /// compiler
+    ///     .add_rule_str(r#"rule flag : demo { strings: $flag = "FLAG" condition: $flag }"#, None)
+    ///     .unwrap();
+    /// let rules = compiler.compile().unwrap();
+    ///
+    /// let listed = rules.iter().collect::<Vec<_>>();
+    /// assert_eq!(listed[0].identifier, "flag");
+    /// assert_eq!(listed[0].tags, vec!["demo"]);
+    /// assert_eq!(listed[0].strings[0].identifier, "$flag");
+    /// ```
+    pub fn iter(&self) -> RuleIterator<'_> {
+        RuleIterator {
+            raw: self.raw_rules(),
+            _rules: PhantomData,
+        }
+    }
+
+    /// Disable a rule, it never matches until enabled again
+    ///
+    /// Changing the rules while they are being scanned is not possible, so
+    /// this needs exclusive access. Rules shared between threads can be
+    /// kept behind a `RwLock` to be switched on and off at runtime.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yara2::*;
+    /// let mut compiler = Compiler::new().unwrap();
+    /// compiler.add_rule_str("rule noisy { condition: true }", None).unwrap();
+    /// let mut rules = compiler.compile().unwrap();
+    ///
+    /// rules.disable("default", "noisy").unwrap();
+    /// assert!(rules.scan_memory(b"data", ScanOptions::default()).unwrap().is_empty());
+    /// ```
+    ///
+    /// # Arguments
+    /// `namespace` - namespace of the rule, `default` unless given when compiling
+    /// `identifier` - name of the rule
+    pub fn disable(&mut self, namespace: &str, identifier: &str) -> Result<()> {
+        let rule = self.find(namespace, identifier)?;
+        unsafe { bindings::yr_rule_disable(rule) };
+        Ok(())
+    }
+
+    /// Enable a rule disabled earlier
+    ///
+    /// # Arguments
+    /// `namespace` - namespace of the rule, `default` unless given when compiling
+    /// `identifier` - name of the rule
+    pub fn enable(&mut self, namespace: &str, identifier: &str) -> Result<()> {
+        let rule = self.find(namespace, identifier)?;
+        unsafe { bindings::yr_rule_enable(rule) };
+        Ok(())
+    }
+
+    /// Whether a rule is enabled
+    ///
+    /// # Arguments
+    /// `namespace` - namespace of the rule, `default` unless given when compiling
+    /// `identifier` - name of the rule
+    pub fn is_enabled(&self, namespace: &str, identifier: &str) -> Result<bool> {
+        let rule = self.find(namespace, identifier)?;
+        Ok(unsafe { (*rule).g_flags } as u32 & bindings::RULE_GFLAGS_DISABLED == 0)
+    }
+
+    /// Disable all the rules with the given tag, returning how many were
+    /// not disabled already
+    ///
+    /// # Arguments
+    /// `tag` - tag of the rules to disable
+    pub fn disable_tagged(&mut self, tag: &str) -> usize {
+        self.disable_where(|rule| rule.tags.iter().any(|t| t == tag))
+    }
+
+    /// Disable all the rules `predicate` returns `true` for, returning how
+    /// many were disabled
+    ///
+    /// The predicate gets the rules as listed by [`Rules::iter`], to select
+    /// them on their metadata for instance. Rules already disabled are
+    /// skipped and not counted.
+    ///
+    /// # Arguments
+    /// `predicate` - whether to disable a rule
+    pub fn disable_where<F>(&mut self, mut predicate: F) -> usize
+    where
+        F: FnMut(&Rule) -> bool,
+    {
+        let mut disabled = 0;
+        for rule in self.raw_rules() {
+            let rule_ref = unsafe { &*rule };
+            if rule_ref.g_flags as u32 & bindings::RULE_GFLAGS_DISABLED != 0 {
+                continue;
+            }
+            if predicate(&Rule::describe(rule_ref)) {
+                unsafe { bindings::yr_rule_disable(rule) };
+                disabled += 1;
+            }
+        }
+        disabled
+    }
+
+    /// Enable all the rules
+    pub fn enable_all(&mut self) {
+        for rule in self.raw_rules() {
+            unsafe { bindings::yr_rule_enable(rule) };
+        }
+    }
+
+    /// Create a [`Scanner`] to scan data against these rules
+    ///
+    /// Reusing a scanner is cheaper than the one-shot `scan_*` methods
+    /// when many scans are done in a row.
+    pub fn scanner(&self) -> Result<Scanner<'_>> {
+        Scanner::new(self)
+    }
+
+    /// Scan a buffer loaded into memory
+    ///
+    /// # Arguments
+    /// `data` - byte array of data to scan
+    /// `options` - settings for this scan
+    pub fn scan_memory(&self, data: &[u8], options: ScanOptions) -> Result<Vec<Rule>> {
+        self.scanner_with(options)?.scan_memory(data)
+    }
+
+    /// Scan a file
+    ///
+    /// # Arguments
+    /// `path` - path to file to scan
+    /// `options` - settings for this scan
+    pub fn scan_file<P: AsRef<Path>>(&self, path: P, options: ScanOptions) -> Result<Vec<Rule>> {
+        self.scanner_with(options)?.scan_file(path)
+    }
+
+    /// Scan a running process
+    ///
+    /// # Arguments
+    /// `pid` - process id to scan
+    /// `options` - settings for this scan
+    pub fn scan_process(
+        &self,
+        pid: std::os::raw::c_int,
+        options: ScanOptions,
+    ) -> Result<Vec<Rule>> {
+        self.scanner_with(options)?.scan_process(pid)
+    }
+
+    /// Scan a running process, also returning its memory regions
+    ///
+    /// See [`Scanner::scan_process_with_regions`].
+    ///
+    /// # Arguments
+    /// `pid` - process id to scan
+    /// `options` - settings for this scan
+    #[cfg(target_os = "linux")]
+    pub fn scan_process_with_regions(
+        &self,
+        pid: std::os::raw::c_int,
+        options: ScanOptions,
+    ) -> Result<crate::ProcessScanResults> {
+        self.scanner_with(options)?.scan_process_with_regions(pid)
+    }
+
+    /// Scan every running process accepted by `filter`
+    ///
+    /// See [`Scanner::scan_all_processes`].
+    ///
+    /// # Arguments
+    /// `options` - settings for each scan
+    /// `filter` - whether a process should be scanned
+    #[cfg(target_os = "linux")]
+    pub fn scan_all_processes<F>(
+        &self,
+        options: ScanOptions,
+        filter: F,
+    ) -> Result<std::collections::BTreeMap<i32, crate::ProcessScan>>
+    where
+        F: FnMut(&crate::ProcessInfo) -> bool,
+    {
+        self.scanner_with(options)?.scan_all_processes(filter)
+    }
+
+    /// Scan a buffer loaded into memory, also returning the data built by
+    /// the modules imported by the rules
+    ///
+    /// # Arguments
+    /// `data` - byte array of data to scan
+    /// `options` - settings for this scan
+    pub fn scan_memory_with_modules(
+        &self,
+        data: &[u8],
+        options: ScanOptions,
+    ) -> Result<ScanResults> {
+        self.scanner_with(options)?.scan_memory_with_modules(data)
+    }
+
+    /// Scan a file, also returning the data built by the modules imported
+    /// by the rules
+    ///
+    /// # Arguments
+    /// `path` - path to file to scan
+    /// `options` - settings for this scan
+    pub fn scan_file_with_modules<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: ScanOptions,
+    ) -> Result<ScanResults> {
+        self.scanner_with(options)?.scan_file_with_modules(path)
+    }
+
+    /// Scan a running process, also returning the data built by the
+    /// modules imported by the rules
+    ///
+    /// # Arguments
+    /// `pid` - process id to scan
+    /// `options` - settings for this scan
+    pub fn scan_process_with_modules(
+        &self,
+        pid: std::os::raw::c_int,
+        options: ScanOptions,
+    ) -> Result<ScanResults> {
+        self.scanner_with(options)?.scan_process_with_modules(pid)
+    }
+
+    pub(crate) fn raw_rules(&self) -> RawRules {
+        RawRules {
+            head: unsafe { (*self.inner).rules_list_head },
+        }
+    }
+
+    fn find(&self, namespace: &str, identifier: &str) -> Result<*mut bindings::YR_RULE> {
+        self.raw_rules()
+            .find(|rule| unsafe {
+                let rule = &**rule;
+                CStr::from_ptr(rule.get_identifier()).to_bytes() == identifier.as_bytes()
+                    && CStr::from_ptr((*rule.get_ns()).get_name()).to_bytes()
+                        == namespace.as_bytes()
+            })
+            .ok_or_else(|| Error::UnknownRule(format!("{}:{}", namespace, identifier)))
+    }
+
+    fn scanner_with(&self, options: ScanOptions) -> Result<Scanner<'_>> {
+        let mut scanner = self.scanner()?;
+        scanner.set_options(options);
+        Ok(scanner)
+    }
+
+    /// Scanner kept in `slot` by a worker thread, created on first use
+    pub(crate) fn worker_scanner<'s, 'r>(
+        &'r self,
+        options: ScanOptions,
+        slot: &'s mut Option<Scanner<'r>>,
+    ) -> Result<&'s mut Scanner<'r>> {
+        if slot.is_none() {
+            *slot = Some(self.scanner_with(options)?);
+        }
+        Ok(slot.as_mut().unwrap())
+    }
+
+    fn load_with<F>(load: F) -> Result<Rules>
+    where
+        F: FnOnce(*mut *mut bindings::YR_RULES) -> i32,
+    {
+        initialize()?;
+        let mut pointer = ptr::null_mut();
+        let result = Error::from_code(load(&mut pointer)).and_then(|_| Rules::new(pointer));
+        finalize()?;
+        result
+    }
+}
+
+/// Glue between a `YR_STREAM` and a rust reader or writer
+///
+/// The first I/O error is kept so it can be reported instead of the
+/// generic code libyara returns when a stream operation fails.
+struct StreamContext<T> {
+    inner: T,
+    error: Option<std::io::Error>,
+}
+
+impl<T> StreamContext<T> {
+    fn new(inner: T) -> Self {
+        StreamContext { inner, error: None }
+    }
+
+    fn into_result<R>(self, result: Result<R>) -> Result<(R, T)> {
+        match self.error {
+            Some(err) => Err(Error::from(err)),
+            None => result.map(|value| (value, self.inner)),
+        }
+    }
+}
+
+/// `fread` like callback used by `yr_rules_load_stream`
+unsafe extern "C" fn stream_read<R: Read>(
+    ptr: *mut c_void,
+    size: bindings::size_t,
+    count: bindings::size_t,
+    user_data: *mut c_void,
+) -> bindings::size_t {
+    let context = &mut *(user_data as *mut StreamContext<R>);
+    let buffer = std::slice::from_raw_parts_mut(ptr as *mut u8, (size * count) as usize);
+    match context.inner.read_exact(buffer) {
+        Ok(_) => count,
+        Err(err) => {
+            context.error.get_or_insert(err);
+            0
+        }
+    }
+}
+
+/// `fwrite` like callback used by `yr_rules_save_stream`
+unsafe extern "C" fn stream_write<W: Write>(
+    ptr: *const c_void,
+    size: bindings::size_t,
+    count: bindings::size_t,
+    user_data: *mut c_void,
+) -> bindings::size_t {
+    let context = &mut *(user_data as *mut StreamContext<W>);
+    let buffer = std::slice::from_raw_parts(ptr as *const u8, (size * count) as usize);
+    match context.inner.write_all(buffer) {
+        Ok(_) => count,
+        Err(err) => {
+            context.error.get_or_insert(err);
+            0
+        }
+    }
+}
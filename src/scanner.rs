@@ -0,0 +1,619 @@
+use crate::bindings;
+use crate::callbacks::{event_callback, scan_callback, Collector, EventHandler, ModuleData};
+use crate::memory_blocks::{
+    Blocks, MemoryBlockIterator, ReaderBlocks, READER_BLOCK_OVERLAP, READER_BLOCK_SIZE,
+};
+use crate::variable::VariableDefiners;
+#[cfg(target_os = "linux")]
+use crate::{memory_regions, processes, ProcessInfo, ProcessScan, ProcessScanResults};
+use crate::{
+    Error, Module, ModuleValue, Result, Rule, Rules, ScanControl, ScanEvent, VariableValue,
+};
+use serde::Serialize;
+#[cfg(target_os = "linux")]
+use std::collections::BTreeMap;
+use std::convert::AsRef;
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::os::raw::c_void;
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+#[cfg(windows)]
+use std::os::windows::io::AsRawHandle;
+use std::panic;
+use std::path::Path;
+use std::ptr;
+use std::time::Duration;
+
+/// Default time allowed for a single scan
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Settings applied to a scan
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use yara2::*;
+/// let options = ScanOptions {
+///     timeout: Duration::from_secs(60),
+///     fast_mode: true,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ScanOptions {
+    /// Maximum time a scan may take, rounded up to whole seconds.
+    /// A zero duration disables the timeout.
+    pub timeout: Duration,
+    /// Stop looking for a string after its first match.
+    pub fast_mode: bool,
+    /// Treat the scanned data as process memory. This is always the case
+    /// when scanning a process.
+    pub process_memory: bool,
+    /// Also return the rules that did not match, with
+    /// [`Rule::matched`] set to `false`.
+    pub report_non_matching: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            timeout: DEFAULT_TIMEOUT,
+            fast_mode: false,
+            process_memory: false,
+            report_non_matching: false,
+        }
+    }
+}
+
+impl ScanOptions {
+    /// Timeout in seconds, as expected by libyara
+    fn timeout_secs(&self) -> i32 {
+        let seconds = self.timeout.as_secs() + u64::from(self.timeout.subsec_nanos() > 0);
+        seconds.min(i32::MAX as u64) as i32
+    }
+
+    /// libyara `SCAN_FLAGS_*` matching these options
+    fn flags(&self) -> i32 {
+        let mut flags = 0;
+        if self.fast_mode {
+            flags |= bindings::SCAN_FLAGS_FAST_MODE;
+        }
+        if self.process_memory {
+            flags |= bindings::SCAN_FLAGS_PROCESS_MEMORY;
+        }
+        flags as i32
+    }
+}
+
+/// Rules reported by a scan, along with the data built by the modules
+/// they import
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ScanResults {
+    pub rules: Vec<Rule>,
+    pub modules: Vec<Module>,
+}
+
+impl ScanResults {
+    /// Data built by the module `name`, if the rules import it
+    pub fn module(&self, name: &str) -> Option<&ModuleValue> {
+        self.modules
+            .iter()
+            .find(|module| module.name == name)
+            .map(|module| &module.value)
+    }
+}
+
+/// Scans data against a set of compiled [`Rules`]
+///
+/// A scanner keeps its settings between scans and can be reused for as
+/// many scans as needed without allocating a new libyara scanner each time.
+/// Each thread should use its own scanner, the rules can be shared.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use yara2::*;
+/// let mut compiler = Compiler::new().unwrap();
+/// compiler.add_rule_str("rule always { condition: true }", None).unwrap();
+/// let rules = compiler.compile().unwrap();
+///
+/// let mut scanner = rules.scanner().unwrap();
+/// scanner.set_timeout(Duration::from_secs(60));
+/// scanner.set_fast_mode(true);
+/// for data in &[&b"first"[..], &b"second"[..]] {
+///     assert_eq!(scanner.scan_memory(data).unwrap().len(), 1);
+/// }
+/// ```
+pub struct Scanner<'r> {
+    inner: *mut bindings::YR_SCANNER,
+    options: ScanOptions,
+    module_data: ModuleData,
+    _rules: &'r Rules,
+}
+
+impl<'r> Drop for Scanner<'r> {
+    fn drop(&mut self) {
+        unsafe {
+            bindings::yr_scanner_destroy(self.inner);
+        }
+    }
+}
+
+// The libyara scanner only claims a thread slot for the duration of a
+// scan, so it can be moved to another thread in between scans.
+unsafe impl<'r> Send for Scanner<'r> {}
+
+impl<'r> Scanner<'r> {
+    /// Create a scanner for the given rules
+    pub fn new(rules: &'r Rules) -> Result<Scanner<'r>> {
+        let mut pointer = ptr::null_mut();
+        Error::from_code(unsafe { bindings::yr_scanner_create(rules.inner, &mut pointer) })?;
+
+        let mut scanner = Scanner {
+            inner: pointer,
+            options: ScanOptions::default(),
+            module_data: ModuleData::new(),
+            _rules: rules,
+        };
+        scanner.set_options(ScanOptions::default());
+        Ok(scanner)
+    }
+
+    /// Options used by the next scans
+    pub fn options(&self) -> ScanOptions {
+        self.options
+    }
+
+    /// Set all the options used by the next scans
+    pub fn set_options(&mut self, options: ScanOptions) -> &mut Self {
+        self.options = options;
+        unsafe {
+            bindings::yr_scanner_set_timeout(self.inner, options.timeout_secs());
+            bindings::yr_scanner_set_flags(self.inner, options.flags());
+        }
+        self
+    }
+
+    /// Set the maximum time a scan may take
+    ///
+    /// libyara works with whole seconds, the duration is rounded up.
+    /// A zero duration disables the timeout.
+    pub fn set_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.set_options(ScanOptions {
+            timeout,
+            ..self.options
+        })
+    }
+
+    /// Enable fast mode, where each string stops at its first match
+    pub fn set_fast_mode(&mut self, fast_mode: bool) -> &mut Self {
+        self.set_options(ScanOptions {
+            fast_mode,
+            ..self.options
+        })
+    }
+
+    /// Also return the rules that did not match
+    pub fn set_report_non_matching(&mut self, report_non_matching: bool) -> &mut Self {
+        self.set_options(ScanOptions {
+            report_non_matching,
+            ..self.options
+        })
+    }
+
+    /// Set the data handed to a module when the rules import it
+    ///
+    /// Modules such as `cuckoo` read additional input from this data
+    /// instead of the scanned data. The scanner keeps the data for all the
+    /// next scans, until it is replaced or cleared.
+    ///
+    /// # Arguments
+    /// `module_name` - name of the module, as imported by the rules
+    /// `data` - bytes handed to the module
+    pub fn set_module_data<D: Into<Vec<u8>>>(&mut self, module_name: &str, data: D) -> &mut Self {
+        self.module_data
+            .insert(module_name.to_string(), data.into());
+        self
+    }
+
+    /// Stop handing data to a module, returning the data it was given
+    pub fn clear_module_data(&mut self, module_name: &str) -> Option<Vec<u8>> {
+        self.module_data.remove(module_name)
+    }
+
+    /// Override the value of an external variable for the next scans
+    ///
+    /// The variable must have been declared with
+    /// [`Compiler::define_variable`](crate::Compiler::define_variable),
+    /// using the same type, otherwise [`Error::UndefinedVariable`] or
+    /// [`Error::InvalidVariableType`] is returned.
+    ///
+    /// # Arguments
+    /// `name` - identifier used in rule conditions
+    /// `value` - new value of the variable
+    ///
+    pub fn define_variable<V: Into<VariableValue>>(&mut self, name: &str, value: V) -> Result<()> {
+        VariableDefiners {
+            integer: bindings::yr_scanner_define_integer_variable,
+            float: bindings::yr_scanner_define_float_variable,
+            boolean: bindings::yr_scanner_define_boolean_variable,
+            string: bindings::yr_scanner_define_string_variable,
+        }
+        .define(self.inner, name, &value.into())
+    }
+
+    /// Scan a buffer loaded into memory
+    ///
+    /// # Arguments
+    /// `data` - byte array of data to scan
+    pub fn scan_memory(&mut self, data: &[u8]) -> Result<Vec<Rule>> {
+        self.collect(false, |scanner| Self::scan_mem(scanner, data))
+            .map(|results| results.rules)
+    }
+
+    /// Scan a file
+    ///
+    /// # Arguments
+    /// `path` - path to file to scan
+    pub fn scan_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<Rule>> {
+        let file = File::open(&path)
+            .map_err(|_| Error::InvalidFile(path.as_ref().to_string_lossy().to_string()))?;
+        self.collect(false, |scanner| Self::scan_raw(scanner, &file))
+            .map(|results| results.rules)
+    }
+
+    /// Scan a running process
+    ///
+    /// # Arguments
+    /// `pid` - process id to scan
+    pub fn scan_process(&mut self, pid: std::os::raw::c_int) -> Result<Vec<Rule>> {
+        self.collect(false, |scanner| unsafe {
+            bindings::yr_scanner_scan_proc(scanner, pid)
+        })
+        .map(|results| results.rules)
+    }
+
+    /// Scan a running process, also returning its memory regions so each
+    /// match can be traced back to the mapping it was found in
+    ///
+    /// The regions are read from `/proc/<pid>/maps` just before the scan,
+    /// mappings created during the scan are missing.
+    ///
+    /// # Arguments
+    /// `pid` - process id to scan
+    #[cfg(target_os = "linux")]
+    pub fn scan_process_with_regions(
+        &mut self,
+        pid: std::os::raw::c_int,
+    ) -> Result<ProcessScanResults> {
+        let regions = memory_regions(pid)?;
+        let rules = self.scan_process(pid)?;
+        Ok(ProcessScanResults { rules, regions })
+    }
+
+    /// Scan every running process accepted by `filter`
+    ///
+    /// Kernel threads and the current process are skipped. A process that
+    /// cannot be scanned, for lack of permissions or because it exited in
+    /// the meantime, has its error in its [`ProcessScan`] instead of
+    /// stopping the sweep. Fails only when `/proc` cannot be listed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use yara2::*;
+    /// let mut compiler = Compiler::new().unwrap();
+    /// compiler.add_rule_str("rule always { condition: true }", None).unwrap();
+    /// let rules = compiler.compile().unwrap();
+    ///
+    /// let mut scanner = rules.scanner().unwrap();
+    /// let results = scanner
+    ///     .scan_all_processes(|process| process.name != "sshd")
+    ///     .unwrap();
+    /// for (pid, scan) in results {
+    ///     println!("{} {:?}: {:?}", pid, scan.process.cmdline, scan.result);
+    /// }
+    /// ```
+    ///
+    /// # Arguments
+    /// `filter` - whether a process should be scanned
+    #[cfg(target_os = "linux")]
+    pub fn scan_all_processes<F>(&mut self, mut filter: F) -> Result<BTreeMap<i32, ProcessScan>>
+    where
+        F: FnMut(&ProcessInfo) -> bool,
+    {
+        Ok(processes()?
+            .into_iter()
+            .filter(|process| filter(process))
+            .map(|process| {
+                let result = self.scan_process(process.pid);
+                (process.pid, ProcessScan { process, result })
+            })
+            .collect())
+    }
+
+    /// Scan a buffer loaded into memory, also returning the data built by
+    /// the modules imported by the rules
+    ///
+    /// # Arguments
+    /// `data` - byte array of data to scan
+    pub fn scan_memory_with_modules(&mut self, data: &[u8]) -> Result<ScanResults> {
+        self.collect(true, |scanner| Self::scan_mem(scanner, data))
+    }
+
+    /// Scan a file, also returning the data built by the modules imported
+    /// by the rules
+    ///
+    /// # Arguments
+    /// `path` - path to file to scan
+    pub fn scan_file_with_modules<P: AsRef<Path>>(&mut self, path: P) -> Result<ScanResults> {
+        let file = File::open(&path)
+            .map_err(|_| Error::InvalidFile(path.as_ref().to_string_lossy().to_string()))?;
+        self.collect(true, |scanner| Self::scan_raw(scanner, &file))
+    }
+
+    /// Scan a running process, also returning the data built by the
+    /// modules imported by the rules
+    ///
+    /// # Arguments
+    /// `pid` - process id to scan
+    pub fn scan_process_with_modules(&mut self, pid: std::os::raw::c_int) -> Result<ScanResults> {
+        self.collect(true, |scanner| unsafe {
+            bindings::yr_scanner_scan_proc(scanner, pid)
+        })
+    }
+
+    /// Scan data from a seekable reader without loading it all in memory
+    ///
+    /// The data is scanned from the current position of the reader to its
+    /// end, match offsets are relative to that position. It is read and
+    /// scanned in blocks of [`READER_BLOCK_SIZE`] bytes, only one block is
+    /// kept in memory at a time. Consecutive blocks overlap by
+    /// [`READER_BLOCK_OVERLAP`] bytes, so only matches longer than that are
+    /// missed when spanning two blocks.
+    ///
+    /// # Arguments
+    /// `reader` - source of the data to scan
+    pub fn scan_reader<R: Read + Seek>(&mut self, reader: R) -> Result<Vec<Rule>> {
+        self.scan_reader_blocks(reader, READER_BLOCK_SIZE, READER_BLOCK_OVERLAP)
+    }
+
+    /// Scan data from a seekable reader, in blocks of `block_size` bytes
+    /// overlapping by `overlap` bytes
+    ///
+    /// See [`Scanner::scan_reader`]. The overlap is capped to half of the
+    /// block size.
+    ///
+    /// # Arguments
+    /// `reader` - source of the data to scan
+    /// `block_size` - number of bytes read and scanned at once
+    /// `overlap` - number of bytes at the end of a block scanned again at
+    /// the start of the next one
+    pub fn scan_reader_blocks<R: Read + Seek>(
+        &mut self,
+        reader: R,
+        block_size: usize,
+        overlap: usize,
+    ) -> Result<Vec<Rule>> {
+        let mut source = ReaderBlocks::new(reader, block_size, overlap)?;
+        let result = self.scan_blocks(&mut source);
+        match source.error.take() {
+            Some(err) => Err(Error::from(err)),
+            None => result,
+        }
+    }
+
+    /// Scan data from a reader that cannot seek, such as a socket or a
+    /// decompression stream
+    ///
+    /// The data is buffered in memory and scanned at once, fails with
+    /// [`Error::DataTooLarge`] when the reader has more than `max_size`
+    /// bytes.
+    ///
+    /// # Arguments
+    /// `reader` - source of the data to scan
+    /// `max_size` - maximum number of bytes buffered
+    pub fn scan_reader_buffered<R: Read>(
+        &mut self,
+        reader: R,
+        max_size: usize,
+    ) -> Result<Vec<Rule>> {
+        let mut data = Vec::new();
+        reader.take(max_size as u64 + 1).read_to_end(&mut data)?;
+        if data.len() > max_size {
+            return Err(Error::DataTooLarge(max_size));
+        }
+        self.scan_memory(&data)
+    }
+
+    /// Scan the blocks of data listed by an iterator
+    ///
+    /// See [`MemoryBlockIterator`].
+    ///
+    /// # Arguments
+    /// `iterator` - source of the blocks to scan
+    pub fn scan_blocks<I: MemoryBlockIterator + ?Sized>(
+        &mut self,
+        iterator: &mut I,
+    ) -> Result<Vec<Rule>> {
+        let mut blocks = Blocks::new(iterator);
+        let mut yr_iterator = blocks.yr_iterator();
+        self.collect(false, |scanner| unsafe {
+            bindings::yr_scanner_scan_mem_blocks(scanner, &mut yr_iterator)
+        })
+        .map(|results| results.rules)
+    }
+
+    /// Scan the blocks of data listed by an iterator, reporting events as
+    /// they happen
+    ///
+    /// See [`Scanner::scan_memory_with`] and [`MemoryBlockIterator`].
+    ///
+    /// # Arguments
+    /// `iterator` - source of the blocks to scan
+    /// `callback` - called for every event of the scan
+    pub fn scan_blocks_with<I, F>(&mut self, iterator: &mut I, callback: F) -> Result<()>
+    where
+        I: MemoryBlockIterator + ?Sized,
+        F: FnMut(ScanEvent) -> ScanControl,
+    {
+        let mut blocks = Blocks::new(iterator);
+        let mut yr_iterator = blocks.yr_iterator();
+        self.scan_with(callback, |scanner| unsafe {
+            bindings::yr_scanner_scan_mem_blocks(scanner, &mut yr_iterator)
+        })
+    }
+
+    /// Scan a buffer loaded into memory, reporting events as they happen
+    ///
+    /// `callback` receives every [`ScanEvent`] and decides whether the
+    /// scan goes on. Returning [`ScanControl::Abort`] stops the scan early,
+    /// which is still a success. A panic of `callback` stops the scan too,
+    /// and carries on once libyara has returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yara2::*;
+    /// let mut compiler = Compiler::new().unwrap();
+    /// compiler.add_rule_str("rule first { condition: true }", None).unwrap();
+    /// compiler.add_rule_str("rule second { condition: true }", None).unwrap();
+    /// let rules = compiler.compile().unwrap();
+    ///
+    /// let mut first = None;
+    /// rules.scanner().unwrap().scan_memory_with(b"data", |event| match event {
+    ///     ScanEvent::RuleMatching(rule) => {
+    ///         first = Some(rule.identifier);
+    ///         ScanControl::Abort
+    ///     }
+    ///     _ => ScanControl::Continue,
+    /// }).unwrap();
+    /// assert_eq!(first.as_deref(), Some("first"));
+    /// ```
+    ///
+    /// # Arguments
+    /// `data` - byte array of data to scan
+    /// `callback` - called for every event of the scan
+    pub fn scan_memory_with<F>(&mut self, data: &[u8], callback: F) -> Result<()>
+    where
+        F: FnMut(ScanEvent) -> ScanControl,
+    {
+        self.scan_with(callback, |scanner| Self::scan_mem(scanner, data))
+    }
+
+    /// Scan a file, reporting events as they happen
+    ///
+    /// See [`Scanner::scan_memory_with`].
+    ///
+    /// # Arguments
+    /// `path` - path to file to scan
+    /// `callback` - called for every event of the scan
+    pub fn scan_file_with<P, F>(&mut self, path: P, callback: F) -> Result<()>
+    where
+        P: AsRef<Path>,
+        F: FnMut(ScanEvent) -> ScanControl,
+    {
+        let file = File::open(&path)
+            .map_err(|_| Error::InvalidFile(path.as_ref().to_string_lossy().to_string()))?;
+        self.scan_with(callback, |scanner| Self::scan_raw(scanner, &file))
+    }
+
+    /// Scan a running process, reporting events as they happen
+    ///
+    /// See [`Scanner::scan_memory_with`].
+    ///
+    /// # Arguments
+    /// `pid` - process id to scan
+    /// `callback` - called for every event of the scan
+    pub fn scan_process_with<F>(&mut self, pid: std::os::raw::c_int, callback: F) -> Result<()>
+    where
+        F: FnMut(ScanEvent) -> ScanControl,
+    {
+        self.scan_with(callback, |scanner| unsafe {
+            bindings::yr_scanner_scan_proc(scanner, pid)
+        })
+    }
+
+    /// Run a scan, collecting the rules reported according to the options
+    /// and, if asked, the modules
+    fn collect<F>(&mut self, collect_modules: bool, scan: F) -> Result<ScanResults>
+    where
+        F: FnOnce(*mut bindings::YR_SCANNER) -> i32,
+    {
+        let mut collector = Collector {
+            rules: Vec::new(),
+            modules: Vec::new(),
+            report_non_matching: self.options.report_non_matching,
+            collect_modules,
+            module_data: &self.module_data,
+        };
+        let user_data = &mut collector as *mut Collector as *mut c_void;
+        self.run(Some(scan_callback), user_data, scan)
+            .map(|_| ScanResults {
+                rules: collector.rules,
+                modules: collector.modules,
+            })
+    }
+
+    /// Run a scan, forwarding its events to `callback`
+    ///
+    /// A panic of `callback` aborts the scan, and is resumed once libyara
+    /// has returned.
+    fn scan_with<C, F>(&mut self, callback: C, scan: F) -> Result<()>
+    where
+        C: FnMut(ScanEvent) -> ScanControl,
+        F: FnOnce(*mut bindings::YR_SCANNER) -> i32,
+    {
+        let mut handler = EventHandler {
+            callback,
+            module_data: &self.module_data,
+            panic: None,
+        };
+        let user_data = &mut handler as *mut EventHandler<C> as *mut c_void;
+        let result = self.run(Some(event_callback::<C>), user_data, scan);
+        if let Some(payload) = handler.panic.take() {
+            panic::resume_unwind(payload);
+        }
+        result
+    }
+
+    /// Run a scan with `callback` installed for its duration only, so the
+    /// scanner never keeps a pointer to `user_data` afterwards
+    fn run<F>(
+        &self,
+        callback: bindings::YR_CALLBACK_FUNC,
+        user_data: *mut c_void,
+        scan: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(*mut bindings::YR_SCANNER) -> i32,
+    {
+        unsafe {
+            bindings::yr_scanner_set_callback(self.inner, callback, user_data);
+        }
+        let result = Error::from_code(scan(self.inner));
+        unsafe {
+            bindings::yr_scanner_set_callback(self.inner, None, ptr::null_mut());
+        }
+        result
+    }
+
+    fn scan_mem(scanner: *mut bindings::YR_SCANNER, data: &[u8]) -> i32 {
+        unsafe { bindings::yr_scanner_scan_mem(scanner, data.as_ptr(), data.len() as _) }
+    }
+
+    #[cfg(unix)]
+    fn scan_raw(scanner: *mut bindings::YR_SCANNER, file: &File) -> i32 {
+        let fd = file.as_raw_fd();
+        unsafe { bindings::yr_scanner_scan_fd(scanner, fd) }
+    }
+
+    #[cfg(windows)]
+    fn scan_raw(scanner: *mut bindings::YR_SCANNER, file: &File) -> i32 {
+        let handle = file.as_raw_handle();
+        unsafe { bindings::yr_scanner_scan_fd(scanner, handle as _) }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::bindings;
+use crate::{Error, Result};
+use serde::Serialize;
+use std::ffi::CString;
+use std::os::raw::c_char;
+
+/// Value of an external variable
+///
+/// Rules reference external variables by name, their type is fixed when
+/// they are declared on the [`Compiler`](crate::Compiler). Scanners can
+/// then override the value, keeping the same type.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub enum VariableValue {
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    String(String),
+}
+
+impl From<i64> for VariableValue {
+    fn from(value: i64) -> Self {
+        VariableValue::Integer(value)
+    }
+}
+
+impl From<i32> for VariableValue {
+    fn from(value: i32) -> Self {
+        VariableValue::Integer(value.into())
+    }
+}
+
+impl From<f64> for VariableValue {
+    fn from(value: f64) -> Self {
+        VariableValue::Float(value)
+    }
+}
+
+impl From<bool> for VariableValue {
+    fn from(value: bool) -> Self {
+        VariableValue::Boolean(value)
+    }
+}
+
+impl From<&str> for VariableValue {
+    fn from(value: &str) -> Self {
+        VariableValue::String(value.to_owned())
+    }
+}
+
+impl From<String> for VariableValue {
+    fn from(value: String) -> Self {
+        VariableValue::String(value)
+    }
+}
+
+/// The `yr_*_define_*_variable` functions of either a compiler or a scanner
+pub(crate) struct VariableDefiners<T> {
+    pub integer: unsafe extern "C" fn(*mut T, *const c_char, i64) -> i32,
+    pub float: unsafe extern "C" fn(*mut T, *const c_char, f64) -> i32,
+    pub boolean: unsafe extern "C" fn(*mut T, *const c_char, i32) -> i32,
+    pub string: unsafe extern "C" fn(*mut T, *const c_char, *const c_char) -> i32,
+}
+
+impl<T> VariableDefiners<T> {
+    /// Define `name` on `target` with the function matching the value type
+    ///
+    /// Scanners can only set variables declared when compiling the rules,
+    /// with the same type.
+    pub fn define(&self, target: *mut T, name: &str, value: &VariableValue) -> Result<()> {
+        let c_name = CString::new(name).map_err(|_| Error::InvalidVariable(name.to_string()))?;
+        let code = match value {
+            VariableValue::Integer(i) => unsafe { (self.integer)(target, c_name.as_ptr(), *i) },
+            VariableValue::Float(f) => unsafe { (self.float)(target, c_name.as_ptr(), *f) },
+            VariableValue::Boolean(b) => unsafe {
+                (self.boolean)(target, c_name.as_ptr(), i32::from(*b))
+            },
+            VariableValue::String(s) => {
+                let c_value = CString::new(s.as_str())
+                    .map_err(|_| Error::InvalidVariable(name.to_string()))?;
+                unsafe { (self.string)(target, c_name.as_ptr(), c_value.as_ptr()) }
+            }
+        };
+        match code as u32 {
+            bindings::ERROR_INVALID_ARGUMENT => Err(Error::UndefinedVariable(name.to_string())),
+            bindings::ERROR_INVALID_EXTERNAL_VARIABLE_TYPE => {
+                Err(Error::InvalidVariableType(name.to_string()))
+            }
+            _ => Error::from_code(code),
+        }
+    }
+}
@@ -1,24 +1,37 @@
 use crate::bindings;
-use crate::callbacks::scan_callback;
-pub use crate::{Error, Result, Rule};
+pub use crate::{Compiler, Error, Result, Rule, Rules, ScanOptions, VariableValue};
 use std::convert::AsRef;
-use std::ffi::CString;
-use std::fs::File;
-use std::os::raw::c_void;
-#[cfg(unix)]
-use std::os::unix::io::AsRawFd;
-#[cfg(windows)]
-use std::os::windows::io::AsRawHandle;
 use std::path::Path;
-use std::ptr;
 use std::sync::Mutex;
 
 lazy_static! {
     static ref INIT_MUTEX: Mutex<()> = Mutex::new(());
 }
 
+/// Initialize the Yara library
+///
+/// Can be called multiple times without problems, libyara keeps count.
+/// Is thread safe.
+pub(crate) fn initialize() -> Result<()> {
+    let _guard = INIT_MUTEX.lock();
+    Error::from_code(unsafe { bindings::yr_initialize() })
+}
+
+/// De-initialize the Yara library
+///
+/// Must not be called more times than [`initialize`].
+/// Is thread safe.
+pub(crate) fn finalize() -> Result<()> {
+    let _guard = INIT_MUTEX.lock();
+    Error::from_code(unsafe { bindings::yr_finalize() })
+}
+
 /// Main entry point for all yara usage
 ///
+/// This is a convenience wrapper around a [`Compiler`] and the [`Rules`]
+/// it produces: rules are compiled on the first scan, after which no more
+/// rules can be added. Use [`Compiler`] directly to share compiled rules.
+///
 /// # Example
 ///
 /// ```
@@ -35,22 +48,9 @@ lazy_static! {
 /// assert_eq!(results.len(), 1);
 /// ```
 pub struct Yara {
-    compiler: *mut bindings::YR_COMPILER,
-    rules: Option<*mut bindings::YR_RULES>,
-}
-
-impl Drop for Yara {
-    fn drop(&mut self) {
-        if let Some(rules) = self.rules {
-            unsafe {
-                bindings::yr_rules_destroy(rules);
-            }
-        }
-        unsafe {
-            bindings::yr_compiler_destroy(self.compiler);
-        }
-        self.finalize().unwrap();
-    }
+    compiler: Option<Compiler>,
+    rules: Option<Rules>,
+    options: ScanOptions,
 }
 
 impl Yara {
@@ -59,38 +59,29 @@ impl Yara {
     /// Can be called multiple times without problems.
     /// Is thread safe.
     pub fn new() -> Result<Yara> {
-        let _guard = INIT_MUTEX.lock();
-        let result = unsafe { bindings::yr_initialize() };
-
-        Error::from_code(result).map_err(Error::from).and_then(|_| {
-            let mut pointer: *mut bindings::YR_COMPILER = ptr::null_mut();
-            let result = unsafe { bindings::yr_compiler_create(&mut pointer) };
-
-            unsafe {
-                bindings::yr_compiler_set_callback(
-                    pointer,
-                    Some(crate::errors::error_callback),
-                    std::ptr::null_mut(),
-                );
-            }
+        Compiler::new().map(|compiler| Yara {
+            compiler: Some(compiler),
+            rules: None,
+            options: ScanOptions::default(),
+        })
+    }
 
-            Error::from_code(result)
-                .map(|()| Yara {
-                    compiler: pointer,
-                    rules: None,
-                })
-                .map_err(Error::from)
+    /// Use rules that were already compiled, e.g. by [`Rules::load`]
+    ///
+    /// No additional rules can be added to the returned instance.
+    pub fn from_rules(rules: Rules) -> Result<Yara> {
+        Ok(Yara {
+            compiler: None,
+            rules: Some(rules),
+            options: ScanOptions::default(),
         })
     }
 
-    /// De-initialize the Yara library
+    /// Declare an external variable used by the rules
     ///
-    /// Must not be called more time than [`initialize`].
-    /// Is thread safe.
-    fn finalize(&self) -> Result<()> {
-        let _guard = INIT_MUTEX.lock();
-        let result = unsafe { bindings::yr_finalize() };
-        Error::from_code(result).map_err(Error::from)
+    /// See [`Compiler::define_variable`].
+    pub fn define_variable<V: Into<VariableValue>>(&mut self, name: &str, value: V) -> Result<()> {
+        self.compiler()?.define_variable(name, value)
     }
 
     /// Add a rule to yara engine
@@ -104,41 +95,64 @@ impl Yara {
     /// `namespace` - optional namespace to store yara results
     ///
     pub fn add_rule_str(&mut self, rule: &str, namespace: Option<&str>) -> Result<()> {
-        match self.rules {
-            Some(_) => Err(Error::AlreadyCompiled),
-            None => unsafe {
-                let c_rule = CString::new(rule).unwrap();
-                match bindings::yr_compiler_add_string(
-                    self.compiler,
-                    c_rule.as_ptr(),
-                    namespace.map_or_else(ptr::null, |ns| {
-                        CString::new(ns).unwrap_or_default().as_ptr()
-                    }),
-                ) {
-                    0 => Ok(()),
-                    count => {
-                        let messages = (0..count + 1)
-                            .map(|_| crate::errors::get_last_error())
-                            .filter_map(|err| err)
-                            .collect::<Vec<_>>();
-                        Err(Error::Multiple(messages))
-                    }
-                }
-            },
-        }
+        self.compiler()?.add_rule_str(rule, namespace)
+    }
+
+    /// Add the rules contained in a file to yara engine
+    ///
+    /// Like [`add_rule_str`](Yara::add_rule_str), this must be done before the first scan.
+    ///
+    /// # Arguments
+    /// `path` - path to a file containing yara rules
+    /// `namespace` - optional namespace to store yara results
+    ///
+    pub fn add_rule_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        namespace: Option<&str>,
+    ) -> Result<()> {
+        self.compiler()?.add_rule_file(path, namespace)
+    }
+
+    /// Recursively add all rule files found under a directory
+    ///
+    /// See [`Compiler::add_rules_dir`].
+    pub fn add_rules_dir<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        pattern: &str,
+        namespace: Option<&str>,
+    ) -> Result<usize> {
+        self.compiler()?.add_rules_dir(path, pattern, namespace)
+    }
+
+    /// Get the compiler, unless rules have already been compiled
+    fn compiler(&mut self) -> Result<&mut Compiler> {
+        self.compiler.as_mut().ok_or(Error::AlreadyCompiled)
     }
 
     /// Compiles rules if needed
-    fn check_rules(&mut self) -> Result<()> {
-        if self.rules.is_none() {
-            let mut pointer = ptr::null_mut();
-            Error::from_code(unsafe {
-                bindings::yr_compiler_get_rules(self.compiler, &mut pointer)
-            })
-            .map_err(Error::from)?;
-            self.rules = Some(pointer);
+    ///
+    /// A failed compilation consumes the compiler, later scans fail with
+    /// [`Error::NoRules`].
+    fn check_rules(&mut self) -> Result<&Rules> {
+        if let Some(compiler) = self.compiler.take() {
+            self.rules = Some(compiler.compile()?);
         }
-        Ok(())
+        self.rules.as_ref().ok_or(Error::NoRules)
+    }
+
+    /// Get the compiled rules, compiling them if needed
+    ///
+    /// Once compiled no more rules can be added, the result can be
+    /// saved with [`Rules::save`] to skip compilation next time.
+    pub fn compiled_rules(&mut self) -> Result<&Rules> {
+        self.check_rules()
+    }
+
+    /// Set the options used by all following scans
+    pub fn set_scan_options(&mut self, options: ScanOptions) {
+        self.options = options;
     }
 
     /// Scan a buffer loaded into memory
@@ -146,26 +160,8 @@ impl Yara {
     /// # Arguments
     /// `data` - byte array of data to scan
     pub fn scan_memory(&mut self, data: &[u8]) -> Result<Vec<Rule>> {
-        self.check_rules()?;
-
-        if let Some(rules) = self.rules {
-            let mut results = Vec::<Rule>::new();
-            Error::from_code(unsafe {
-                bindings::yr_rules_scan_mem(
-                    rules,
-                    data.as_ptr(),
-                    data.len() as _,
-                    0,
-                    Some(scan_callback),
-                    &mut results as *mut Vec<_> as *mut c_void,
-                    10,
-                )
-            })
-            .map_err(Error::from)
-            .map(|_| results)
-        } else {
-            Ok(Vec::new())
-        }
+        let options = self.options;
+        self.check_rules()?.scan_memory(data, options)
     }
 
     /// Scan a file
@@ -173,19 +169,8 @@ impl Yara {
     /// # Arguments
     /// `path` - path to file to scan
     pub fn scan_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<Rule>> {
-        self.check_rules()?;
-
-        if let Some(rules) = self.rules {
-            let mut results = Vec::<Rule>::new();
-            File::open(&path)
-                .map_err(|_| Error::InvalidFile(path.as_ref().to_str().unwrap().to_string()))
-                .and_then(|ref file| unsafe {
-                    Error::from_code(self.rules_scan_raw(&mut *rules, file, 10, &mut results))
-                        .map(|_| results)
-                })
-        } else {
-            Ok(Vec::new())
-        }
+        let options = self.options;
+        self.check_rules()?.scan_file(path, options)
     }
 
     /// Scan a running process
@@ -193,66 +178,7 @@ impl Yara {
     /// # Arguments
     /// `pid` - process id to scan
     pub fn scan_process(&mut self, pid: std::os::raw::c_int) -> Result<Vec<Rule>> {
-        self.check_rules()?;
-
-        if let Some(rules) = self.rules {
-            let mut results = Vec::<Rule>::new();
-            Error::from_code(unsafe {
-                bindings::yr_rules_scan_proc(
-                    rules,
-                    pid,
-                    0,
-                    Some(scan_callback),
-                    &mut results as *mut Vec<_> as *mut c_void,
-                    10,
-                )
-            })
-            .map_err(Error::from)
-            .map(|_| results)
-        } else {
-            Ok(Vec::new())
-        }
-    }
-
-    #[cfg(unix)]
-    pub fn rules_scan_raw(
-        &self,
-        rules: &mut bindings::YR_RULES,
-        file: &File,
-        timeout: i32,
-        results: &mut Vec<Rule>,
-    ) -> i32 {
-        let fd = file.as_raw_fd();
-        unsafe {
-            bindings::yr_rules_scan_fd(
-                rules,
-                fd,
-                0,
-                Some(scan_callback),
-                results as *mut Vec<_> as *mut c_void,
-                timeout,
-            )
-        }
-    }
-
-    #[cfg(windows)]
-    pub fn rules_scan_raw(
-        &self,
-        rules: &mut bindings::YR_RULES,
-        file: &File,
-        timeout: i32,
-        results: &mut Vec<Rule>,
-    ) -> i32 {
-        let handle = file.as_raw_handle();
-        unsafe {
-            bindings::yr_rules_scan_fd(
-                rules,
-                handle as _,
-                0,
-                Some(scan_callback),
-                results as *mut Vec<_> as *mut c_void,
-                timeout,
-            )
-        }
+        let options = self.options;
+        self.check_rules()?.scan_process(pid, options)
     }
 }
@@ -0,0 +1,36 @@
+#![cfg(target_os = "linux")]
+extern crate yara2;
+
+use std::process::Command;
+use yara2::*;
+
+#[test]
+fn scan_all_processes() {
+    let mut child = Command::new("sleep")
+        .arg("30")
+        .spawn()
+        .expect("Couldn't start process");
+    let pid = child.id() as i32;
+
+    let listed = processes().expect("Couldn't list processes");
+    assert!(listed.iter().all(|p| p.pid != std::process::id() as i32));
+    assert!(listed.iter().all(|p| !p.cmdline.is_empty()));
+
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str("rule always { condition: true }", None)
+        .expect("Couldn't add rule");
+    let rules = compiler.compile().expect("Couldn't compile rules");
+
+    let results = rules.scan_all_processes(ScanOptions::default(), |process| process.pid == pid);
+    child.kill().expect("Couldn't stop process");
+    child.wait().expect("Couldn't wait for process");
+    let results = results.expect("Couldn't list processes");
+
+    assert_eq!(results.len(), 1);
+    let scan = &results[&pid];
+    assert_eq!(scan.process.name, "sleep");
+    assert_eq!(scan.process.cmdline, vec!["sleep", "30"]);
+    let rules = scan.result.as_ref().expect("error scanning process!");
+    assert_eq!(rules[0].identifier, "always");
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "async")]
+extern crate yara2;
+
+mod common;
+
+use common::flag_rules;
+use std::sync::Arc;
+use yara2::*;
+
+#[tokio::test]
+async fn scan_memory_async() {
+    let scanner = AsyncScanner::new(Arc::new(flag_rules()), 2);
+    let results = scanner
+        .scan_memory_async(b"a FLAG".to_vec())
+        .await
+        .expect("error scanning memory!");
+    assert_eq!(results.len(), 1);
+}
+
+#[tokio::test]
+async fn scan_file_async() {
+    let scanner = AsyncScanner::new(Arc::new(flag_rules()), 2);
+    let scans = [
+        "./tests/data1.txt",
+        "./tests/data2.txt",
+        "./tests/data1.txt",
+    ]
+    .iter()
+    .map(|path| {
+        let scanner = scanner.clone();
+        tokio::spawn(async move { scanner.scan_file_async(path).await })
+    })
+    .collect::<Vec<_>>();
+
+    let mut counts = Vec::new();
+    for scan in scans {
+        let results = scan.await.unwrap().expect("error scanning file!");
+        counts.push(results.len());
+    }
+    assert_eq!(counts, vec![1, 0, 1]);
+}
+
+#[tokio::test]
+async fn scan_directory_async() {
+    let mut scanner = DirectoryScanner::new(Arc::new(flag_rules()));
+    scanner.include("data*.txt").expect("Couldn't add pattern");
+
+    let mut scan = scanner.scan_async("./tests");
+    let mut matching = 0;
+    let mut total = 0;
+    while let Some((_, result)) = scan.recv().await {
+        total += 1;
+        matching += result.expect("error scanning file!").len();
+    }
+    assert_eq!((total, matching), (2, 1));
+}
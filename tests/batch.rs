@@ -0,0 +1,44 @@
+#![cfg(feature = "rayon")]
+extern crate yara2;
+
+mod common;
+
+use common::flag_rules;
+use std::path::PathBuf;
+use yara2::*;
+
+#[test]
+fn scan_many_in_order() {
+    let rules = flag_rules();
+    let inputs = (0..100)
+        .map(|i| {
+            if i % 3 == 0 {
+                ScanInput::from(format!("item {} has a FLAG", i).into_bytes())
+            } else {
+                ScanInput::from(format!("item {}", i).into_bytes())
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let results = rules.scan_many(inputs, ScanOptions::default());
+    assert_eq!(results.len(), 100);
+    for (i, scan) in results.iter().enumerate() {
+        let matches = scan.result.as_ref().expect("error scanning memory!");
+        assert_eq!(matches.len(), if i % 3 == 0 { 1 } else { 0 });
+    }
+}
+
+#[test]
+fn scan_many_files() {
+    let rules = flag_rules();
+    let inputs = vec![
+        PathBuf::from("./tests/data1.txt"),
+        PathBuf::from("./tests/data2.txt"),
+        PathBuf::from("./tests/missing.txt"),
+    ];
+
+    let results = rules.scan_many(inputs, ScanOptions::default());
+    assert_eq!(results[0].result.as_ref().unwrap().len(), 1);
+    assert!(results[1].result.as_ref().unwrap().is_empty());
+    assert!(results[2].result.is_err());
+}
@@ -0,0 +1,10 @@
+use yara2::*;
+
+/// Rules matching `FLAG`, from tests/rules/flag.yar
+pub fn flag_rules() -> Rules {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_file("./tests/rules/flag.yar", None)
+        .expect("Couldn't add rule file");
+    compiler.compile().expect("Couldn't compile rules")
+}
@@ -0,0 +1,31 @@
+extern crate yara2;
+
+use yara2::*;
+
+#[test]
+fn compiler_rules() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str(
+            r#"rule is_awesome {
+  strings:
+    $rust = "rust" nocase
+
+  condition:
+    $rust
+}"#,
+            Some("awesome"),
+        )
+        .expect("Couldn't add rule");
+    let rules = compiler.compile().expect("Couldn't compile rules");
+
+    let none = rules
+        .scan_memory(b"this is a string", ScanOptions::default())
+        .expect("error scanning memory!");
+    let some = rules
+        .scan_memory(b"this is a rust string", ScanOptions::default())
+        .expect("error scanning memory!");
+    assert!(none.is_empty());
+    assert_eq!(some.len(), 1);
+    assert_eq!(some[0].namespace, "awesome");
+}
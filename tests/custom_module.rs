@@ -0,0 +1,132 @@
+#![cfg(feature = "test-modules")]
+extern crate yara2;
+
+use std::collections::BTreeMap;
+use yara2::*;
+
+struct RustTests;
+
+fn double(args: &[ModuleValue]) -> ModuleValue {
+    match args[0].as_integer() {
+        Some(i) => ModuleValue::Integer(i * 2),
+        None => ModuleValue::Undefined,
+    }
+}
+
+impl CustomModule for RustTests {
+    fn name(&self) -> &str {
+        "rust_tests"
+    }
+
+    fn declarations(&self) -> Vec<Declaration> {
+        vec![
+            Declaration::Integer("size"),
+            Declaration::String("data"),
+            Declaration::Structure("nested", vec![Declaration::Float("ratio")]),
+            Declaration::Array("values", Box::new(Declaration::Integer("value"))),
+            Declaration::Dictionary("names", Box::new(Declaration::String("name"))),
+            Declaration::Function {
+                name: "double",
+                arguments: vec![ValueType::Integer],
+                returns: ValueType::Integer,
+                code: double,
+            },
+        ]
+    }
+
+    fn load(&self, data: &[u8], module_data: Option<&[u8]>) -> ModuleValue {
+        let mut nested = BTreeMap::new();
+        nested.insert("ratio".to_string(), ModuleValue::Float(0.5));
+        let mut names = BTreeMap::new();
+        names.insert("rust".to_string(), ModuleValue::String("crab".to_string()));
+
+        let mut fields = BTreeMap::new();
+        fields.insert("size".to_string(), ModuleValue::Integer(data.len() as i64));
+        fields.insert("nested".to_string(), ModuleValue::Structure(nested));
+        fields.insert(
+            "values".to_string(),
+            ModuleValue::Array(vec![ModuleValue::Integer(1), ModuleValue::Integer(2)]),
+        );
+        fields.insert("names".to_string(), ModuleValue::Dictionary(names));
+        // Not declared, ignored
+        fields.insert("other".to_string(), ModuleValue::Integer(1));
+        if let Some(module_data) = module_data {
+            fields.insert(
+                "data".to_string(),
+                ModuleValue::String(String::from_utf8_lossy(module_data).to_string()),
+            );
+        }
+        ModuleValue::Structure(fields)
+    }
+}
+
+#[test]
+fn custom_module() {
+    register_module(RustTests).expect("Couldn't register module");
+
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str(
+            r#"import "rust_tests"
+
+rule uses_rust {
+  condition:
+    rust_tests.size == 4 and
+    rust_tests.nested.ratio == 0.5 and
+    rust_tests.values[1] == 2 and
+    rust_tests.names["rust"] == "crab" and
+    rust_tests.double(21) == 42
+}
+
+rule module_data {
+  condition:
+    rust_tests.data == "extra"
+}"#,
+            None,
+        )
+        .expect("Couldn't add rule");
+    let rules = compiler.compile().expect("Couldn't compile rules");
+
+    let mut scanner = rules.scanner().expect("Couldn't create scanner");
+    let results = scanner
+        .scan_memory(b"data")
+        .expect("error scanning memory!");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].identifier, "uses_rust");
+
+    scanner.set_module_data("rust_tests", "extra");
+    let results = scanner
+        .scan_memory_with_modules(b"data")
+        .expect("error scanning memory!");
+    assert_eq!(results.rules.len(), 2);
+    let module = results.module("rust_tests").expect("missing module");
+    assert_eq!(module.get("other"), None);
+    assert_eq!(
+        module.get("data").and_then(ModuleValue::as_str),
+        Some("extra")
+    );
+}
+
+#[test]
+fn unknown_custom_module() {
+    struct NotBuilt;
+
+    impl CustomModule for NotBuilt {
+        fn name(&self) -> &str {
+            "not_built"
+        }
+
+        fn declarations(&self) -> Vec<Declaration> {
+            Vec::new()
+        }
+
+        fn load(&self, _data: &[u8], _module_data: Option<&[u8]>) -> ModuleValue {
+            ModuleValue::Undefined
+        }
+    }
+
+    match register_module(NotBuilt) {
+        Err(Error::UnknownModule(name)) => assert_eq!(name, "not_built"),
+        _ => panic!("module should not be built in"),
+    }
+}
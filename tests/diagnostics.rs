@@ -0,0 +1,78 @@
+extern crate yara2;
+
+use yara2::*;
+
+#[test]
+fn compile_diagnostics() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    let result = compiler.add_rule_str(
+        r#"rule broken {
+  condition:
+    $missing
+}"#,
+        None,
+    );
+
+    match result {
+        Err(Error::Multiple(diagnostics)) => {
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].severity, Severity::Error);
+            assert_eq!(diagnostics[0].file, None);
+            assert_eq!(diagnostics[0].line, 3);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn compile_diagnostics_file() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    match compiler.add_rule_file("./tests/rules/notes.txt", None) {
+        Err(Error::InvalidRuleFile(_, diagnostics)) => {
+            let file = diagnostics[0].file.as_deref().unwrap_or_default();
+            assert!(file.ends_with("notes.txt"));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn compile_diagnostics_per_compiler() {
+    let handles = (1..8)
+        .map(|padding| {
+            std::thread::spawn(move || {
+                let rule = format!(
+                    "{}rule broken {{ condition: $missing }}",
+                    "\n".repeat(padding)
+                );
+                let mut compiler = Compiler::new().expect("Couldn't init compiler");
+                match compiler.add_rule_str(&rule, None) {
+                    Err(Error::Multiple(diagnostics)) => {
+                        diagnostics.len() == 1 && diagnostics[0].line == padding as i32 + 1
+                    }
+                    _ => false,
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        assert!(handle.join().expect("compiling thread panicked"));
+    }
+}
+
+#[test]
+fn compile_warnings() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_file("./tests/warnings/slow.yar", None)
+        .expect("Couldn't add rule file");
+    let rules = compiler.compile().expect("Couldn't compile rules");
+
+    let warnings = rules.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].severity, Severity::Warning);
+    let file = warnings[0].file.as_deref().unwrap_or_default();
+    assert!(file.ends_with("slow.yar"));
+    assert_eq!(warnings[0].line, 2);
+}
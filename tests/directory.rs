@@ -0,0 +1,65 @@
+extern crate yara2;
+
+mod common;
+
+use common::flag_rules;
+use std::path::PathBuf;
+use std::sync::Arc;
+use yara2::*;
+
+#[test]
+fn scan_directory() {
+    let mut scanner = DirectoryScanner::new(Arc::new(flag_rules()));
+    scanner
+        .include("*.txt")
+        .expect("Couldn't add pattern")
+        .exclude("rules")
+        .expect("Couldn't add pattern")
+        .set_threads(2);
+
+    let mut results = scanner
+        .scan("./tests")
+        .map(|(path, result)| (path, result.expect("error scanning file!")))
+        .collect::<Vec<_>>();
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let paths = results
+        .iter()
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from("./tests/data1.txt"),
+            PathBuf::from("./tests/data2.txt")
+        ]
+    );
+    assert_eq!(results[0].1.len(), 1);
+    assert!(results[1].1.is_empty());
+}
+
+#[test]
+fn scan_directory_max_file_size() {
+    let mut scanner = DirectoryScanner::new(Arc::new(flag_rules()));
+    scanner.include("*.txt").expect("Couldn't add pattern");
+    scanner.set_max_file_size(Some(0));
+
+    assert_eq!(scanner.scan("./tests").count(), 0);
+}
+
+#[test]
+fn invalid_pattern() {
+    let mut scanner = DirectoryScanner::new(Arc::new(flag_rules()));
+    assert!(scanner.include("[").is_err());
+}
+
+#[test]
+fn scan_missing_directory() {
+    let scanner = DirectoryScanner::new(Arc::new(flag_rules()));
+    let results = scanner.scan("./tests/missing").collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+    match &results[0].1 {
+        Err(Error::Io(err)) => assert_eq!(err.kind(), std::io::ErrorKind::NotFound),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
@@ -0,0 +1,99 @@
+extern crate yara2;
+
+use yara2::*;
+
+fn rules() -> Rules {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str(
+            r#"rule noisy : experimental {
+  meta:
+    quality = "low"
+
+  condition:
+    true
+}
+
+rule trusted {
+  meta:
+    quality = "high"
+
+  condition:
+    true
+}
+
+rule draft : experimental {
+  condition:
+    true
+}"#,
+            None,
+        )
+        .expect("Couldn't add rule");
+    compiler.compile().expect("Couldn't compile rules")
+}
+
+fn matching(rules: &Rules) -> Vec<String> {
+    rules
+        .scan_memory(b"data", ScanOptions::default())
+        .expect("error scanning memory!")
+        .into_iter()
+        .map(|rule| rule.identifier)
+        .collect()
+}
+
+#[test]
+fn disable_and_enable() {
+    let mut rules = rules();
+
+    rules
+        .disable("default", "noisy")
+        .expect("Couldn't disable rule");
+    assert!(!rules.is_enabled("default", "noisy").unwrap());
+    assert_eq!(matching(&rules), vec!["trusted", "draft"]);
+
+    rules
+        .enable("default", "noisy")
+        .expect("Couldn't enable rule");
+    assert!(rules.is_enabled("default", "noisy").unwrap());
+    assert_eq!(matching(&rules), vec!["noisy", "trusted", "draft"]);
+}
+
+#[test]
+fn disable_unknown_rule() {
+    let mut rules = rules();
+    match rules.disable("other", "noisy") {
+        Err(Error::UnknownRule(name)) => assert_eq!(name, "other:noisy"),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn disable_in_bulk() {
+    let mut rules = rules();
+
+    assert_eq!(rules.disable_tagged("experimental"), 2);
+    assert_eq!(matching(&rules), vec!["trusted"]);
+
+    rules.enable_all();
+    let low_quality = rules.disable_where(|rule| {
+        rule.metadata.iter().any(|meta| {
+            meta.identifier == "quality" && meta.value == MetadataValue::String("low".to_string())
+        })
+    });
+    assert_eq!(low_quality, 1);
+    assert_eq!(matching(&rules), vec!["trusted", "draft"]);
+}
+
+#[test]
+fn disable_in_bulk_twice() {
+    let mut rules = rules();
+
+    assert_eq!(rules.disable_tagged("experimental"), 2);
+    assert_eq!(rules.disable_tagged("experimental"), 0);
+
+    rules
+        .enable("default", "draft")
+        .expect("Couldn't enable rule");
+    assert_eq!(rules.disable_where(|_| true), 2);
+    assert_eq!(matching(&rules), Vec::<String>::new());
+}
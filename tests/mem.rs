@@ -31,3 +31,33 @@ fn yara_mem() {
     assert!(none.len() == 0);
     assert!(some.len() != 0);
 }
+
+#[test]
+fn yara_mem_invalid_rule() {
+    let mut yara = Yara::new().expect("Couldn't init yara");
+    assert!(yara.add_rule_str("rule broken {", None).is_err());
+
+    match yara.scan_memory(b"this is a string") {
+        Err(Error::InvalidRule) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match yara.scan_memory(b"this is a string") {
+        Err(Error::NoRules) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn yara_mem_rule_after_invalid_rule() {
+    let mut yara = Yara::new().expect("Couldn't init yara");
+    assert!(yara.add_rule_str("rule broken {", None).is_err());
+
+    match yara.add_rule_str("rule valid { condition: true }", None) {
+        Err(Error::InvalidRule) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match yara.add_rule_file("./tests/rules/flag.yar", None) {
+        Err(Error::InvalidRule) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
@@ -0,0 +1,76 @@
+extern crate yara2;
+
+use yara2::*;
+
+/// Blocks of a fake process, fetched lazily
+struct Regions {
+    regions: Vec<(u64, Vec<u8>)>,
+    current: usize,
+    fetched: usize,
+}
+
+impl MemoryBlockIterator for Regions {
+    fn first(&mut self) -> Option<MemoryBlock> {
+        self.current = 0;
+        self.next()
+    }
+
+    fn next(&mut self) -> Option<MemoryBlock> {
+        let (base, data) = self.regions.get(self.current)?;
+        self.current += 1;
+        Some(MemoryBlock {
+            base: *base,
+            size: data.len(),
+        })
+    }
+
+    fn fetch(&mut self, block: &MemoryBlock) -> Option<&[u8]> {
+        self.fetched += 1;
+        self.regions
+            .iter()
+            .find(|(base, _)| *base == block.base)
+            .map(|(_, data)| data.as_slice())
+    }
+}
+
+#[test]
+fn scan_blocks() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str(
+            r#"rule has_flag {
+  strings:
+    $flag = "FLAG"
+
+  condition:
+    $flag
+}"#,
+            None,
+        )
+        .expect("Couldn't add rule");
+    let rules = compiler.compile().expect("Couldn't compile rules");
+
+    let mut regions = Regions {
+        regions: vec![
+            (0x1000, b"FLAG at the start".to_vec()),
+            (0x7fff_0000, b"no match here".to_vec()),
+            (0x7fff_8000, b"and a FLAG".to_vec()),
+        ],
+        current: 0,
+        fetched: 0,
+    };
+    let results = rules
+        .scanner()
+        .expect("Couldn't create scanner")
+        .scan_blocks(&mut regions)
+        .expect("error scanning blocks!");
+
+    assert_eq!(regions.fetched, 3);
+    let matches = &results[0].strings[0].matches;
+    assert_eq!(matches.len(), 2);
+    assert_eq!((matches[0].base, matches[0].offset), (0x1000, 0x1000));
+    assert_eq!(
+        (matches[1].base, matches[1].offset),
+        (0x7fff_8000, 0x7fff_8006)
+    );
+}
@@ -0,0 +1,61 @@
+extern crate yara2;
+
+use yara2::*;
+
+#[test]
+fn module_data() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str(
+            r#"import "tests"
+
+rule has_data {
+  condition:
+    tests.module_data == "some data"
+}"#,
+            None,
+        )
+        .expect("Couldn't add rule");
+    let rules = compiler.compile().expect("Couldn't compile rules");
+
+    let mut scanner = rules.scanner().expect("Couldn't create scanner");
+    assert!(scanner
+        .scan_memory(b"data")
+        .expect("error scanning memory!")
+        .is_empty());
+
+    scanner.set_module_data("tests", "some data");
+    assert_eq!(
+        scanner
+            .scan_memory(b"data")
+            .expect("error scanning memory!")
+            .len(),
+        1
+    );
+
+    let mut imported = Vec::new();
+    scanner
+        .scan_memory_with(b"data", |event| {
+            if let ScanEvent::ModuleImported(module) = event {
+                imported.push(module);
+            }
+            ScanControl::Continue
+        })
+        .expect("error scanning memory!");
+    assert_eq!(
+        imported[0]
+            .value
+            .get("module_data")
+            .and_then(ModuleValue::as_str),
+        Some("some data")
+    );
+
+    assert_eq!(
+        scanner.clear_module_data("tests"),
+        Some(b"some data".to_vec())
+    );
+    assert!(scanner
+        .scan_memory(b"data")
+        .expect("error scanning memory!")
+        .is_empty());
+}
@@ -0,0 +1,56 @@
+extern crate yara2;
+
+use yara2::*;
+
+#[test]
+fn module_data() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str(
+            r#"import "tests"
+
+rule uses_tests {
+  condition:
+    tests.constants.one == 1
+}"#,
+            None,
+        )
+        .expect("Couldn't add rule");
+    let rules = compiler.compile().expect("Couldn't compile rules");
+
+    let results = rules
+        .scan_file_with_modules("./tests/data1.txt", ScanOptions::default())
+        .expect("error scanning file!");
+    assert_eq!(results.rules.len(), 1);
+
+    let tests = results.module("tests").expect("missing tests module");
+    let constants = tests.get("constants").expect("missing constants");
+    assert_eq!(
+        constants.get("one").and_then(ModuleValue::as_integer),
+        Some(1)
+    );
+    assert_eq!(
+        constants.get("foo").and_then(ModuleValue::as_str),
+        Some("foo")
+    );
+    assert_eq!(
+        tests
+            .get("string_array")
+            .and_then(|array| array.index(1))
+            .and_then(ModuleValue::as_str),
+        Some("bar")
+    );
+    assert_eq!(
+        tests
+            .get("integer_dict")
+            .and_then(|dict| dict.get("foo"))
+            .and_then(ModuleValue::as_integer),
+        Some(1)
+    );
+
+    // Plain scans do not pay for converting the modules
+    let rules_only = rules
+        .scan_file("./tests/data1.txt", ScanOptions::default())
+        .expect("error scanning file!");
+    assert_eq!(rules_only.len(), 1);
+}
@@ -0,0 +1,47 @@
+extern crate yara2;
+
+use yara2::*;
+
+#[test]
+fn report_non_matching() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str(
+            r#"rule has_flag {
+  strings:
+    $flag = "FLAG"
+
+  condition:
+    $flag
+}
+
+rule never {
+  condition:
+    false
+}"#,
+            None,
+        )
+        .expect("Couldn't add rule");
+    let rules = compiler.compile().expect("Couldn't compile rules");
+
+    let matching = rules
+        .scan_file("./tests/data1.txt", ScanOptions::default())
+        .expect("error scanning file!");
+    assert_eq!(matching.len(), 1);
+    assert!(matching[0].matched);
+
+    let all = rules
+        .scan_file(
+            "./tests/data1.txt",
+            ScanOptions {
+                report_non_matching: true,
+                ..Default::default()
+            },
+        )
+        .expect("error scanning file!");
+    let outcomes = all
+        .iter()
+        .map(|rule| (rule.identifier.as_str(), rule.matched))
+        .collect::<Vec<_>>();
+    assert_eq!(outcomes, vec![("has_flag", true), ("never", false)]);
+}
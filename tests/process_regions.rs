@@ -0,0 +1,45 @@
+#![cfg(target_os = "linux")]
+extern crate yara2;
+
+use std::process::Command;
+use yara2::*;
+
+#[test]
+fn process_regions() {
+    let mut child = Command::new("sleep")
+        .arg("30")
+        .spawn()
+        .expect("Couldn't start process");
+    let pid = child.id() as i32;
+
+    let regions = memory_regions(pid).expect("Couldn't read regions");
+    assert!(regions
+        .iter()
+        .any(|region| region.path.as_deref() == Some("[stack]")));
+
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str(
+            r#"rule elf_header {
+  strings:
+    $elf = { 7F 45 4C 46 }
+
+  condition:
+    $elf
+}"#,
+            None,
+        )
+        .expect("Couldn't add rule");
+    let rules = compiler.compile().expect("Couldn't compile rules");
+    let results = rules.scan_process_with_regions(pid, ScanOptions::default());
+    child.kill().expect("Couldn't stop process");
+    child.wait().expect("Couldn't wait for process");
+    let results = results.expect("error scanning process!");
+
+    let matches = &results.rules[0].strings[0].matches;
+    assert!(!matches.is_empty());
+    for m in matches {
+        let region = results.region_for(m).expect("match outside of regions");
+        assert!(region.contains(m.offset as u64));
+    }
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "profiling")]
+extern crate yara2;
+
+use yara2::*;
+
+#[test]
+fn profile_rules() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str(
+            r#"rule slow {
+  strings:
+    $re = /[a-z]+[0-9]+[a-z]+/
+
+  condition:
+    $re
+}
+
+rule fast {
+  strings:
+    $flag = "FLAG"
+
+  condition:
+    $flag
+}"#,
+            None,
+        )
+        .expect("Couldn't add rule");
+    let mut rules = compiler.compile().expect("Couldn't compile rules");
+
+    let data = "abc123def ".repeat(100_000);
+    for _ in 0..3 {
+        rules
+            .scan_memory(data.as_bytes(), ScanOptions::default())
+            .expect("error scanning memory!");
+    }
+
+    let profile = rules.profile();
+    assert_eq!(profile.rules.len(), 2);
+    assert_eq!(profile.slowest(1)[0].identifier, "slow");
+    assert!(profile.slowest(1)[0].time_cost > std::time::Duration::from_secs(0));
+    assert_eq!(profile.most_atoms(5).len(), 2);
+
+    rules.reset_profile();
+    let profile = rules.profile();
+    assert_eq!(profile.total, std::time::Duration::from_secs(0));
+    assert!(profile
+        .rules
+        .iter()
+        .all(|rule| rule.time_cost == std::time::Duration::from_secs(0)));
+}
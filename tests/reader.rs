@@ -0,0 +1,83 @@
+extern crate yara2;
+
+mod common;
+
+use common::flag_rules;
+use std::fs::File;
+use std::io::Cursor;
+use yara2::*;
+
+#[test]
+fn scan_reader() {
+    let rules = flag_rules();
+    let mut scanner = rules.scanner().expect("Couldn't create scanner");
+
+    let file = File::open("./tests/data1.txt").expect("Couldn't open file");
+    let results = scanner.scan_reader(file).expect("error scanning reader!");
+    assert_eq!(results.len(), 1);
+
+    let file = File::open("./tests/data2.txt").expect("Couldn't open file");
+    let results = scanner.scan_reader(file).expect("error scanning reader!");
+    assert!(results.is_empty());
+}
+
+#[test]
+fn scan_reader_blocks() {
+    let rules = flag_rules();
+    let mut scanner = rules.scanner().expect("Couldn't create scanner");
+
+    let mut data = vec![0u8; 100];
+    data.extend_from_slice(b"FLAG");
+    let results = scanner
+        .scan_reader_blocks(Cursor::new(&data), 32, 8)
+        .expect("error scanning reader!");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].strings[0].matches[0].offset, 100);
+}
+
+#[test]
+fn scan_reader_across_blocks() {
+    let rules = flag_rules();
+    let mut scanner = rules.scanner().expect("Couldn't create scanner");
+
+    let mut data = vec![0u8; READER_BLOCK_SIZE + 1024];
+    data[READER_BLOCK_SIZE - 2..READER_BLOCK_SIZE + 2].copy_from_slice(b"FLAG");
+    let results = scanner
+        .scan_reader(Cursor::new(&data))
+        .expect("error scanning reader!");
+    assert_eq!(results.len(), 1);
+    let matches = &results[0].strings[0].matches;
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].offset, READER_BLOCK_SIZE - 2);
+}
+
+#[test]
+fn scan_reader_from_position() {
+    let rules = flag_rules();
+    let mut scanner = rules.scanner().expect("Couldn't create scanner");
+
+    let mut reader = Cursor::new(b"FLAG then another FLAG".to_vec());
+    reader.set_position(4);
+    let results = scanner
+        .scan_reader_blocks(reader, 8, 4)
+        .expect("error scanning reader!");
+    let matches = &results[0].strings[0].matches;
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].offset, 14);
+}
+
+#[test]
+fn scan_reader_buffered() {
+    let rules = flag_rules();
+    let mut scanner = rules.scanner().expect("Couldn't create scanner");
+
+    let results = scanner
+        .scan_reader_buffered(&b"some FLAG"[..], 64)
+        .expect("error scanning reader!");
+    assert_eq!(results.len(), 1);
+
+    match scanner.scan_reader_buffered(&b"some FLAG"[..], 4) {
+        Err(Error::DataTooLarge(4)) => {}
+        _ => panic!("data should be too large"),
+    }
+}
@@ -0,0 +1,50 @@
+extern crate yara2;
+
+use yara2::*;
+
+#[test]
+fn yara_rules_dir() {
+    let mut yara = Yara::new().expect("Couldn't init yara");
+    let count = yara
+        .add_rules_dir("./tests/rules", "*.yar", None)
+        .expect("Couldn't add rules");
+    assert_eq!(count, 2);
+
+    let results = yara
+        .scan_memory(b"a rust FLAG")
+        .expect("error scanning memory!");
+    assert_eq!(results.len(), 2);
+}
+
+#[test]
+fn yara_rule_file_error() {
+    let mut yara = Yara::new().expect("Couldn't init yara");
+    match yara.add_rule_file("./tests/rules/notes.txt", None) {
+        Err(Error::InvalidRuleFile(file, errors)) => {
+            assert!(file.ends_with("notes.txt"));
+            assert!(!errors.is_empty());
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn yara_rules_dir_stops_at_error() {
+    let mut yara = Yara::new().expect("Couldn't init yara");
+    match yara.add_rules_dir("./tests/rules", "*", None) {
+        Err(Error::InvalidRuleFile(file, errors)) => {
+            assert!(file.ends_with("notes.txt"));
+            assert!(!errors.is_empty());
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn yara_rules_dir_missing() {
+    let mut yara = Yara::new().expect("Couldn't init yara");
+    match yara.add_rules_dir("./tests/missing", "*.yar", None) {
+        Err(Error::Io(err)) => assert_eq!(err.kind(), std::io::ErrorKind::NotFound),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -0,0 +1,64 @@
+extern crate yara2;
+
+use yara2::*;
+
+#[test]
+fn list_rules() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str(
+            r#"private rule helper {
+  condition:
+    true
+}
+
+rule has_flag : demo {
+  meta:
+    author = "tests"
+
+  strings:
+    $flag = "FLAG"
+    $other = "other" nocase
+
+  condition:
+    helper and any of them
+}"#,
+            Some("listing"),
+        )
+        .expect("Couldn't add rule");
+    let rules = compiler.compile().expect("Couldn't compile rules");
+
+    let listed = rules.iter().collect::<Vec<_>>();
+    let identifiers = listed
+        .iter()
+        .map(|rule| rule.identifier.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(identifiers, vec!["helper", "has_flag"]);
+
+    let rule = &listed[1];
+    assert!(!rule.matched);
+    assert_eq!(rule.namespace, "listing");
+    assert_eq!(rule.tags, vec!["demo"]);
+    assert_eq!(rule.metadata.len(), 1);
+    let strings = rule
+        .strings
+        .iter()
+        .map(|string| string.identifier.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(strings, vec!["$flag", "$other"]);
+    assert!(rule.strings.iter().all(|string| string.matches.is_empty()));
+}
+
+#[test]
+fn list_rules_after_scan() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str("rule always { condition: true }", None)
+        .expect("Couldn't add rule");
+    let rules = compiler.compile().expect("Couldn't compile rules");
+
+    rules
+        .scan_memory(b"data", ScanOptions::default())
+        .expect("error scanning memory!");
+    assert_eq!((&rules).into_iter().count(), 1);
+}
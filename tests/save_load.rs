@@ -0,0 +1,40 @@
+extern crate yara2;
+
+use yara2::*;
+
+const RULE: &str = r#"rule is_awesome {
+  strings:
+    $rust = "rust" nocase
+
+  condition:
+    $rust
+}"#;
+
+#[test]
+fn yara_save_load_stream() {
+    let mut yara = Yara::new().expect("Couldn't init yara");
+    yara.add_rule_str(RULE, None).expect("Couldn't add rule");
+
+    let mut compiled = Vec::new();
+    yara.compiled_rules()
+        .expect("Couldn't compile rules")
+        .save_stream(&mut compiled)
+        .expect("Couldn't save rules");
+
+    let rules = Rules::load_stream(compiled.as_slice()).expect("Couldn't load rules");
+    let mut yara = Yara::from_rules(rules).expect("Couldn't init yara");
+    let some = yara
+        .scan_memory(b"this is a rust string")
+        .expect("error scanning memory!");
+    assert_eq!(some.len(), 1);
+    assert!(yara.add_rule_str(RULE, None).is_err());
+}
+
+#[test]
+fn yara_load_missing_file() {
+    match Rules::load("./tests/does-not-exist.bin") {
+        Err(Error::InvalidFile(_)) => {}
+        Err(err) => panic!("unexpected error: {}", err),
+        Ok(_) => panic!("loaded rules from a missing file"),
+    }
+}
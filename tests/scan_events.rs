@@ -0,0 +1,102 @@
+extern crate yara2;
+
+use yara2::*;
+
+fn two_rules() -> Rules {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str(
+            r#"rule has_flag {
+  strings:
+    $flag = "FLAG"
+
+  condition:
+    $flag
+}
+
+rule always {
+  condition:
+    true
+}"#,
+            None,
+        )
+        .expect("Couldn't add rule");
+    compiler.compile().expect("Couldn't compile rules")
+}
+
+#[test]
+fn scan_events() {
+    let rules = two_rules();
+    let mut scanner = rules.scanner().expect("Couldn't create scanner");
+
+    let mut matching = Vec::new();
+    let mut not_matching = Vec::new();
+    let mut finished = false;
+    scanner
+        .scan_file_with("./tests/data2.txt", |event| {
+            match event {
+                ScanEvent::RuleMatching(rule) => matching.push(rule.identifier),
+                ScanEvent::RuleNotMatching(rule) => not_matching.push(rule.identifier),
+                ScanEvent::ScanFinished => finished = true,
+                _ => {}
+            }
+            ScanControl::Continue
+        })
+        .expect("error scanning file!");
+
+    assert_eq!(matching, vec!["always".to_string()]);
+    assert_eq!(not_matching, vec!["has_flag".to_string()]);
+    assert!(finished);
+}
+
+#[test]
+fn scan_events_abort() {
+    let rules = two_rules();
+    let mut scanner = rules.scanner().expect("Couldn't create scanner");
+
+    let mut events = 0;
+    let mut finished = false;
+    scanner
+        .scan_memory_with(b"FLAG", |event| {
+            events += 1;
+            match event {
+                ScanEvent::RuleMatching(_) => ScanControl::Abort,
+                ScanEvent::ScanFinished => {
+                    finished = true;
+                    ScanControl::Continue
+                }
+                _ => ScanControl::Continue,
+            }
+        })
+        .expect("error scanning memory!");
+
+    assert_eq!(events, 1);
+    assert!(!finished);
+
+    // The scanner is still usable after an aborted scan
+    let results = scanner
+        .scan_memory(b"FLAG")
+        .expect("error scanning memory!");
+    assert_eq!(results.len(), 2);
+}
+
+#[test]
+fn scan_events_panic() {
+    let rules = two_rules();
+    let mut scanner = rules.scanner().expect("Couldn't create scanner");
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        scanner.scan_memory_with(b"FLAG", |event| match event {
+            ScanEvent::RuleMatching(_) => panic!("callback failed"),
+            _ => ScanControl::Continue,
+        })
+    }));
+    let payload = result.expect_err("the panic was not resumed");
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"callback failed"));
+
+    // The scanner is still usable after a panic in the callback
+    let results = scanner
+        .scan_memory(b"FLAG")
+        .expect("error scanning memory!");
+    assert_eq!(results.len(), 2);
+}
@@ -0,0 +1,71 @@
+extern crate yara2;
+
+use std::time::Duration;
+use yara2::*;
+
+#[test]
+fn scanner_reuse() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str(
+            r#"rule has_flag {
+  strings:
+    $flag = "FLAG"
+
+  condition:
+    $flag
+}"#,
+            None,
+        )
+        .expect("Couldn't add rule");
+    let rules = compiler.compile().expect("Couldn't compile rules");
+
+    let mut scanner = rules.scanner().expect("Couldn't create scanner");
+    scanner
+        .set_timeout(Duration::from_secs(30))
+        .set_fast_mode(true);
+
+    for _ in 0..10 {
+        let some = scanner
+            .scan_file("./tests/data1.txt")
+            .expect("error scanning file!");
+        let none = scanner
+            .scan_file("./tests/data2.txt")
+            .expect("error scanning file!");
+        assert!(none.is_empty());
+        assert_eq!(some.len(), 1);
+    }
+}
+
+#[test]
+fn scanner_fast_mode() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str(
+            r#"rule has_flag {
+  strings:
+    $flag = "FLAG"
+
+  condition:
+    $flag
+}"#,
+            None,
+        )
+        .expect("Couldn't add rule");
+    let rules = compiler.compile().expect("Couldn't compile rules");
+
+    let all = rules
+        .scan_memory(b"FLAG FLAG", ScanOptions::default())
+        .expect("error scanning memory!");
+    let first = rules
+        .scan_memory(
+            b"FLAG FLAG",
+            ScanOptions {
+                fast_mode: true,
+                ..Default::default()
+            },
+        )
+        .expect("error scanning memory!");
+    assert_eq!(all[0].strings[0].matches.len(), 2);
+    assert_eq!(first[0].strings[0].matches.len(), 1);
+}
@@ -0,0 +1,79 @@
+extern crate yara2;
+
+use yara2::*;
+
+#[test]
+fn string_flags_and_kinds() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str(
+            r#"rule strings {
+  strings:
+    $text = "FLAG" nocase wide ascii fullword
+    $hex = { 46 4C [1-2] 47 }
+    $regex = /FL.G/s
+    $xored = "secret" xor
+
+  condition:
+    any of them
+}"#,
+            None,
+        )
+        .expect("Couldn't add rule");
+    let rules = compiler.compile().expect("Couldn't compile rules");
+
+    let results = rules
+        .scan_memory(b"a FLAG here", ScanOptions::default())
+        .expect("error scanning memory!");
+    let strings = &results[0].strings;
+
+    let text = &strings[0];
+    assert_eq!(text.kind, StringKind::Text);
+    assert!(text.flags.contains(
+        StringFlags::NO_CASE | StringFlags::WIDE | StringFlags::ASCII | StringFlags::FULL_WORD
+    ));
+    assert!(!text.flags.is_chained());
+    assert_eq!(
+        serde_json::to_value(text.flags).unwrap(),
+        serde_json::json!(["NO_CASE", "ASCII", "WIDE", "FULL_WORD"])
+    );
+    assert_eq!(text.matches.len(), 1);
+
+    assert_eq!(strings[1].kind, StringKind::Hex);
+    assert!(strings[1].flags.contains(StringFlags::HEXADECIMAL));
+
+    assert_eq!(strings[2].kind, StringKind::Regex);
+    assert!(strings[2]
+        .flags
+        .contains(StringFlags::REGEXP | StringFlags::DOT_ALL));
+
+    assert_eq!(strings[3].kind, StringKind::Text);
+    assert!(strings[3].flags.contains(StringFlags::XOR));
+    assert!(!strings[3].flags.contains(StringFlags::NO_CASE));
+}
+
+#[test]
+fn chained_hex_string() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str(
+            r#"rule chained {
+  strings:
+    $hex = { 46 4C [300-400] 47 }
+
+  condition:
+    $hex
+}"#,
+            None,
+        )
+        .expect("Couldn't add rule");
+    let rules = compiler.compile().expect("Couldn't compile rules");
+
+    // libyara splits the string on the large jump, every piece is chained
+    let rule = rules.iter().next().expect("no rule compiled");
+    assert!(rule.strings.len() > 1);
+    assert!(rule
+        .strings
+        .iter()
+        .all(|string| string.kind == StringKind::Hex && string.flags.is_chained()));
+}
@@ -0,0 +1,42 @@
+extern crate yara2;
+
+use std::sync::Arc;
+use std::thread;
+use yara2::*;
+
+#[test]
+fn rules_shared_between_threads() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .add_rule_str(
+            r#"rule is_awesome {
+  strings:
+    $rust = "rust" nocase
+
+  condition:
+    $rust
+}"#,
+            None,
+        )
+        .expect("Couldn't add rule");
+    let rules = Arc::new(compiler.compile().expect("Couldn't compile rules"));
+
+    let handles = (0..8)
+        .map(|_| {
+            let rules = rules.clone();
+            thread::spawn(move || {
+                (0..100)
+                    .map(|_| {
+                        rules
+                            .scan_memory(b"this is a rust string", ScanOptions::default())
+                            .expect("error scanning memory!")
+                    })
+                    .all(|results| results.len() == 1 && results[0].strings[0].matches.len() == 1)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        assert!(handle.join().expect("scanning thread panicked"));
+    }
+}
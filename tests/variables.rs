@@ -0,0 +1,49 @@
+extern crate yara2;
+
+use yara2::*;
+
+const RULE: &str = r#"rule is_script {
+  condition:
+    extension == "ps1" and size_limit > 10 and not trusted
+}"#;
+
+#[test]
+fn external_variables() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    compiler
+        .define_variable("extension", "")
+        .expect("Couldn't define variable");
+    compiler
+        .define_variable("size_limit", 100)
+        .expect("Couldn't define variable");
+    compiler
+        .define_variable("trusted", false)
+        .expect("Couldn't define variable");
+    compiler
+        .add_rule_str(RULE, None)
+        .expect("Couldn't add rule");
+    let rules = compiler.compile().expect("Couldn't compile rules");
+
+    let mut scanner = rules.scanner().expect("Couldn't create scanner");
+    assert!(scanner.scan_memory(b"").expect("error scanning").is_empty());
+
+    scanner
+        .define_variable("extension", "ps1")
+        .expect("Couldn't override variable");
+    assert_eq!(scanner.scan_memory(b"").expect("error scanning").len(), 1);
+
+    match scanner.define_variable("filename", "evil.ps1") {
+        Err(Error::UndefinedVariable(name)) => assert_eq!(name, "filename"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match scanner.define_variable("trusted", 1) {
+        Err(Error::InvalidVariableType(name)) => assert_eq!(name, "trusted"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn undeclared_external_variable() {
+    let mut compiler = Compiler::new().expect("Couldn't init compiler");
+    assert!(compiler.add_rule_str(RULE, None).is_err());
+}